@@ -18,6 +18,15 @@ fn main() {
             .expect("Failed to set windows resources");
     }
 
+    println!("cargo::rerun-if-changed=java/build.gradle.kts");
+    println!("cargo::rerun-if-changed=java/src");
+    println!("cargo::rerun-if-changed=res/windows");
+    println!("cargo::rerun-if-changed=locales/");
+
+    // Builds without server support do not embed the server launcher
+    if env::var("CARGO_FEATURE_SERVER").is_err() {
+        return;
+    }
     let proj_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     let mut server_launcher = PathBuf::from(&proj_dir);
     server_launcher.push("ServerLauncher.jar");
@@ -43,8 +52,4 @@ fn main() {
         .status()
         .expect("Gradle build should succeed");
     }
-    println!("cargo::rerun-if-changed=java/build.gradle.kts");
-    println!("cargo::rerun-if-changed=java/src");
-    println!("cargo::rerun-if-changed=res/windows");
-    println!("cargo::rerun-if-changed=locales/");
 }
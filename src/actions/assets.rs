@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::errors::InstallerError;
+
+const RESOURCES_URL: &str = "https://resources.download.minecraft.net/";
+
+static RESOURCES_URL_OVERRIDE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+static INDEX_URL_OVERRIDE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// A mirror must be laid out like Mojang's CDN: `<base>/<first two hash chars>/<hash>`.
+pub fn set_resources_url(url: &str) -> Result<(), InstallerError> {
+    let _ = RESOURCES_URL_OVERRIDE.set(base_url(url)?);
+    Ok(())
+}
+
+/// Keeps the path of the index URL in the launch json, e.g. `<base>/v1/packages/<hash>/<id>.json`.
+pub fn set_index_url(url: &str) -> Result<(), InstallerError> {
+    let _ = INDEX_URL_OVERRIDE.set(base_url(url)?);
+    Ok(())
+}
+
+fn base_url(url: &str) -> Result<String, InstallerError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| {
+        InstallerError::from(t!(
+            "client.error.invalid_assets_url",
+            url = url,
+            error = e.to_string()
+        ))
+    })?;
+    let mut base = parsed.to_string();
+    if !base.ends_with('/') {
+        base.push('/');
+    }
+    Ok(base)
+}
+
+fn index_url(url: &str) -> String {
+    match (INDEX_URL_OVERRIDE.get(), reqwest::Url::parse(url)) {
+        (Some(base), Ok(parsed)) => base.to_owned() + parsed.path().trim_start_matches('/'),
+        _ => url.to_owned(),
+    }
+}
+
+fn resources_url() -> &'static str {
+    RESOURCES_URL_OVERRIDE
+        .get()
+        .map(String::as_str)
+        .unwrap_or(RESOURCES_URL)
+}
+
+#[derive(Deserialize)]
+struct AssetIndex {
+    objects: std::collections::HashMap<String, AssetObject>,
+}
+
+#[derive(Deserialize)]
+struct AssetObject {
+    hash: String,
+    size: u64,
+}
+
+pub async fn download_assets(
+    sender: &UnboundedSender<(f32, String)>,
+    game_dir: &Path,
+    vanilla_launch_json: &Value,
+    concurrency: usize,
+) -> Result<(), InstallerError> {
+    let asset_index = &vanilla_launch_json["assetIndex"];
+    let (Some(id), Some(url), Some(sha1)) = (
+        asset_index["id"].as_str(),
+        asset_index["url"].as_str(),
+        asset_index["sha1"].as_str(),
+    ) else {
+        return Err(InstallerError::from(t!("client.error.no_asset_index")));
+    };
+    let assets_dir = game_dir.join("assets");
+
+    let index_file = assets_dir.join("indexes").join(id.to_owned() + ".json");
+    if !is_present(&index_file, sha1)? {
+        let actual =
+            crate::net::download_file(&index_url(url), &index_file, asset_index["size"].as_u64())
+                .await?;
+        crate::net::verify_sha1(&(id.to_owned() + ".json"), sha1, &actual)?;
+    }
+    let index = serde_json::from_slice::<AssetIndex>(&std::fs::read(&index_file)?)?;
+
+    let objects_dir = assets_dir.join("objects");
+    let mut missing = Vec::new();
+    for object in index.objects.into_values() {
+        let file = object_path(&objects_dir, &object.hash);
+        if !is_present(&file, &object.hash)? {
+            missing.push((object, file));
+        }
+    }
+    missing.sort_by(|a, b| a.0.hash.cmp(&b.0.hash));
+    missing.dedup_by(|a, b| a.0.hash == b.0.hash);
+
+    let mut progress = super::ProgressTracker::new(sender, 0.85, 1.0, missing.len());
+    progress.message(t!(
+        "client.info.downloading_assets",
+        count = crate::format::format_count(missing.len() as u64),
+        concurrency = concurrency
+    ));
+
+    let resources_url = resources_url();
+    let mut downloads = tokio::task::JoinSet::new();
+    for (object, file) in missing {
+        // Keep at most `concurrency` downloads running at once
+        while downloads.len() >= concurrency {
+            finish_download(&mut downloads, &mut progress).await?;
+        }
+        downloads.spawn(async move {
+            let url = format!("{resources_url}{}/{}", &object.hash[..2], object.hash);
+            let actual = crate::net::download_file(&url, &file, Some(object.size)).await?;
+            crate::net::verify_sha1(&object.hash, &object.hash, &actual)
+        });
+    }
+    while !downloads.is_empty() {
+        finish_download(&mut downloads, &mut progress).await?;
+    }
+
+    Ok(())
+}
+
+async fn finish_download(
+    downloads: &mut tokio::task::JoinSet<Result<(), InstallerError>>,
+    progress: &mut super::ProgressTracker<'_>,
+) -> Result<(), InstallerError> {
+    match downloads.join_next().await {
+        Some(Ok(result)) => result?,
+        Some(Err(e)) => {
+            return Err(InstallerError::from(t!(
+                "client.error.asset_download_failed",
+                error = e.to_string()
+            )));
+        }
+        None => return Ok(()),
+    }
+    progress.advance(String::new());
+    Ok(())
+}
+
+fn object_path(objects_dir: &Path, hash: &str) -> PathBuf {
+    objects_dir.join(&hash[..2]).join(hash)
+}
+
+fn is_present(file: &Path, sha1: &str) -> Result<bool, InstallerError> {
+    if !file.exists() {
+        return Ok(false);
+    }
+    Ok(crate::net::sha1(&std::fs::read(file)?).eq_ignore_ascii_case(sha1))
+}
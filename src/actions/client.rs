@@ -1,7 +1,11 @@
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use base64::{Engine, prelude::BASE64_STANDARD_NO_PAD};
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value, json};
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -14,6 +18,20 @@ use crate::{
     },
 };
 
+/// Asset objects are shared between versions and are not listed.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct InstallResult {
+    pub created: Vec<PathBuf>,
+    pub replaced: Vec<PathBuf>,
+    pub profile: Option<ProfileChange>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileChange {
+    Added(String),
+    Updated(String),
+}
+
 pub async fn install(
     sender: UnboundedSender<(f32, String)>,
     version: MinecraftVersion,
@@ -24,7 +42,16 @@ pub async fn install(
     location: PathBuf,
     create_profile: bool,
     include_flap: bool,
-) -> Result<(), InstallerError> {
+    portable: bool,
+    asset_concurrency: Option<usize>,
+    release_time_mtime: bool,
+) -> Result<InstallResult, InstallerError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if portable {
+        prepare_portable_root(&sender, &location, create_profile)?;
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = portable;
     #[cfg(not(target_arch = "wasm32"))]
     if !location.exists() {
         return Err(InstallerError::from(t!(
@@ -32,6 +59,8 @@ pub async fn install(
             dir = location.to_string_lossy()
         )));
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    check_game_dir(&location)?;
     let message = if cfg!(target_arch = "wasm32") {
         t!(
             "client.info.installation_start_web",
@@ -50,6 +79,24 @@ pub async fn install(
     };
     let _ = sender.send((0.2, message.into()));
 
+    #[cfg(not(target_arch = "wasm32"))]
+    if create_profile {
+        let launcher = Launcher::detect(&location).ok_or(InstallerError::from(t!(
+            "client.error.could_not_find_launcher_profiles_json"
+        )))?;
+        let _ = sender.send((
+            0.2,
+            t!(
+                "client.info.using_launcher_profiles",
+                file = location.join(launcher.profiles_file_name()).display()
+            )
+            .into(),
+        ));
+        if launcher == Launcher::MicrosoftStore {
+            super::warn(&sender, 0.2, t!("client.warning.microsoft_store_launcher"))?;
+        }
+    }
+
     let calamus_gen = match generation {
         Some(g) => g,
         None => meta::fetch_intermediary_generations().await?.stable,
@@ -77,22 +124,31 @@ pub async fn install(
     let flap_jar = profile_dir.join("flap.jar");
 
     let flap_jar_file = if include_flap {
-        Some(maven::get_latest_release_file("flap").await?)
+        Some(maven::get_flap_file().await?)
     } else {
         None
     };
 
+    // Profile names can get long, so files are written through the extended-length form
     #[cfg(not(target_arch = "wasm32"))]
-    {
-        let vanilla_profile_dir = versions_dir.join(&vanilla_profile_name);
-        let profile_dir = versions_dir.join(&profile_name);
-        if std::fs::exists(&vanilla_profile_dir).unwrap_or_default() {
-            std::fs::remove_dir_all(&vanilla_profile_dir)?;
-        }
-        if std::fs::exists(&profile_dir).unwrap_or_default() {
-            std::fs::remove_dir_all(&profile_dir)?;
+    let versions_dir = super::long_path(&versions_dir);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let (mut created, mut replaced) = (Vec::new(), Vec::new());
+    #[cfg(not(target_arch = "wasm32"))]
+    for name in [&vanilla_profile_name, &profile_name] {
+        let dir = versions_dir.join(name);
+        if std::fs::exists(&dir).unwrap_or_default() {
+            std::fs::remove_dir_all(&dir)?;
+            replaced.push(location.join("versions").join(name));
+        } else {
+            created.push(location.join("versions").join(name));
         }
     }
+    #[cfg(target_arch = "wasm32")]
+    let (created, replaced) = (Vec::new(), Vec::new());
+    #[cfg(not(target_arch = "wasm32"))]
+    let record = versions_dir.join(&profile_name).join(INSTALL_RECORD);
 
     #[cfg(target_arch = "wasm32")]
     let mut buf = std::io::Cursor::new(Vec::new());
@@ -133,14 +189,19 @@ pub async fn install(
     writer.create_dir(&vanilla_profile_name)?;
     writer.create_dir(&profile_name)?;
 
+    let vanilla_json_file = format!("{}/{}.json", vanilla_profile_name, vanilla_profile_name);
+    let ornithe_json_file = format!("{}/{}.json", profile_name, profile_name);
     writer.write_file(
-        &format!("{}/{}.json", vanilla_profile_name, vanilla_profile_name),
-        vanilla_launch_json.as_bytes(),
-    )?;
-    writer.write_file(
-        &format!("{}/{}.json", profile_name, profile_name),
-        &serde_json::to_vec(&ornithe_launch_json)?,
+        &vanilla_json_file,
+        &super::to_json(&serde_json::from_str::<Value>(&vanilla_launch_json)?)?,
     )?;
+    writer.write_file(&ornithe_json_file, &super::to_json(&ornithe_launch_json)?)?;
+    // Reinstalling the same version then leaves the jsons looking unchanged to file sync tools
+    if release_time_mtime {
+        let time = std::time::SystemTime::from(version.release_time);
+        writer.set_modified(&vanilla_json_file, time)?;
+        writer.set_modified(&ornithe_json_file, time)?;
+    }
 
     #[cfg(target_arch = "wasm32")]
     {
@@ -151,36 +212,638 @@ pub async fn install(
         });
     }
 
-    if create_profile && cfg!(not(target_arch = "wasm32")) {
-        update_profiles(location, profile_name, version, loader_type, calamus_gen)?;
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(concurrency) = asset_concurrency {
+        let vanilla_json = serde_json::from_str::<Value>(&vanilla_launch_json)?;
+        super::assets::download_assets(
+            &sender,
+            &super::long_path(&location),
+            &vanilla_json,
+            concurrency,
+        )
+        .await?;
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = asset_concurrency;
+
+    let profile = if create_profile && cfg!(not(target_arch = "wasm32")) {
+        Some(update_profiles(
+            &sender,
+            location,
+            profile_name,
+            &version.id,
+            loader_type,
+            calamus_gen,
+        )?)
+    } else {
+        None
+    };
+    let result = InstallResult {
+        created,
+        replaced,
+        profile,
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    std::fs::write(record, super::to_json(&result)?)?;
+
+    let _ = sender.send((1.0, t!("client.info.done").into()));
+
+    Ok(result)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const INSTALL_RECORD: &str = "ornithe-install.json";
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Launcher {
+    MicrosoftStore,
+    Java,
+}
+
+impl Launcher {
+    pub fn profiles_file_name(&self) -> &str {
+        match self {
+            Launcher::MicrosoftStore => "launcher_profiles_microsoft_store.json",
+            Launcher::Java => "launcher_profiles.json",
+        }
+    }
+
+    /// The Microsoft Store launcher takes precedence as it may leave
+    /// an old launcher_profiles.json from a previous installation behind.
+    pub fn detect(game_dir: &Path) -> Option<Launcher> {
+        [Launcher::MicrosoftStore, Launcher::Java]
+            .into_iter()
+            .find(|launcher| game_dir.join(launcher.profiles_file_name()).exists())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn repair_profile(
+    sender: UnboundedSender<(f32, String)>,
+    location: PathBuf,
+    minecraft_version: &str,
+    loader_type: LoaderType,
+    generation: Option<u32>,
+) -> Result<(), InstallerError> {
+    let (profile_name, calamus_gen) = find_installed_profile(
+        &location.join("versions"),
+        minecraft_version,
+        &loader_type,
+        generation,
+    )?
+    .ok_or(InstallerError::from(t!(
+        "client.error.no_installed_profile",
+        version = minecraft_version,
+        loader = loader_type.get_localized_name()
+    )))?;
+    update_profile(
+        sender,
+        location,
+        profile_name,
+        minecraft_version,
+        loader_type,
+        calamus_gen,
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn update_profile(
+    sender: UnboundedSender<(f32, String)>,
+    location: PathBuf,
+    profile_name: String,
+    minecraft_version: &str,
+    loader_type: LoaderType,
+    calamus_gen: u32,
+) -> Result<(), InstallerError> {
+    let _ = sender.send((
+        0.5,
+        t!("client.info.repairing_profile", profile = profile_name).into(),
+    ));
+    update_profiles(
+        &sender,
+        location,
+        profile_name,
+        minecraft_version,
+        loader_type,
+        calamus_gen,
+    )?;
+    let _ = sender.send((1.0, t!("client.info.done").into()));
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verify_profile(
+    location: &Path,
+    minecraft_version: &str,
+    loader_type: LoaderType,
+    generation: Option<u32>,
+) -> Result<(String, Vec<String>), InstallerError> {
+    let versions_dir = location.join("versions");
+    let Some((profile_name, calamus_gen)) =
+        find_installed_profile(&versions_dir, minecraft_version, &loader_type, generation)?
+    else {
+        // find_installed_profile skips jsons it cannot read, so point out broken ones by name
+        let suffix = format!("-{minecraft_version}");
+        if let Ok(entries) = std::fs::read_dir(&versions_dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let file = entry.path().join(name.clone() + ".json");
+                if name.contains(loader_type.get_name())
+                    && name.ends_with(&suffix)
+                    && read_profile_json(&file).is_err()
+                {
+                    return Ok((
+                        name,
+                        vec![t!("client.verify.malformed_json", file = file.display()).into()],
+                    ));
+                }
+            }
+        }
+        return Err(InstallerError::from(t!(
+            "client.error.no_installed_profile",
+            version = minecraft_version,
+            loader = loader_type.get_localized_name()
+        )));
+    };
+
+    let mut problems = Vec::new();
+    let json = read_profile_json(
+        &versions_dir
+            .join(&profile_name)
+            .join(profile_name.clone() + ".json"),
+    )?;
+    if json["mainClass"].as_str().is_none_or(str::is_empty) {
+        problems.push(t!("client.verify.no_main_class").into());
+    }
+
+    let libraries = json["libraries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|library| library["name"].as_str())
+        .collect::<Vec<_>>();
+    let loader_library = loader_type.library_prefix();
+    for expected in [
+        loader_library.as_str(),
+        "net.ornithemc:calamus-intermediary:",
+    ] {
+        if !libraries
+            .iter()
+            .any(|library| library.starts_with(expected))
+        {
+            problems.push(
+                t!(
+                    "client.verify.missing_library",
+                    library = expected.trim_end_matches(':')
+                )
+                .into(),
+            );
+        }
+    }
+
+    for argument in json["arguments"]["jvm"].as_array().into_iter().flatten() {
+        if let Some(agent) = argument
+            .as_str()
+            .and_then(|arg| arg.strip_prefix("-javaagent:"))
+            && !Path::new(agent).exists()
+        {
+            problems.push(t!("client.verify.missing_file", file = agent).into());
+        }
+    }
+
+    let vanilla_profile_name = format!("{minecraft_version}-gen{calamus_gen}");
+    let vanilla_file = versions_dir
+        .join(&vanilla_profile_name)
+        .join(vanilla_profile_name.clone() + ".json");
+    if !vanilla_file.exists() {
+        problems.push(
+            t!(
+                "client.verify.missing_vanilla_profile",
+                profile = vanilla_profile_name
+            )
+            .into(),
+        );
+    } else {
+        match read_profile_json(&vanilla_file) {
+            Ok(vanilla) if vanilla["id"].as_str() == Some(&vanilla_profile_name) => {}
+            Ok(_) => problems.push(
+                t!(
+                    "client.verify.vanilla_id_mismatch",
+                    file = vanilla_file.display()
+                )
+                .into(),
+            ),
+            Err(_) => problems.push(
+                t!(
+                    "client.verify.malformed_json",
+                    file = vanilla_file.display()
+                )
+                .into(),
+            ),
+        }
+    }
+
+    Ok((profile_name, problems))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_profile_json(file: &Path) -> Result<Value, InstallerError> {
+    Ok(serde_json::from_slice(&std::fs::read(file)?)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct UninstallPlan {
+    pub directories: Vec<PathBuf>,
+    pub profiles: Vec<String>,
+    location: PathBuf,
+}
+
+/// A vanilla `<version>-gen<N>` directory is only included if the recorded install of one
+/// of these versions created it and no other installed version inherits from it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn plan_uninstall(
+    location: PathBuf,
+    minecraft_version: &str,
+    loader_type: LoaderType,
+    generation: Option<u32>,
+) -> Result<UninstallPlan, InstallerError> {
+    let versions_dir = location.join("versions");
+    let installed =
+        find_installed_profiles(&versions_dir, minecraft_version, &loader_type, generation)?;
+    if installed.is_empty() {
+        return Err(InstallerError::from(t!(
+            "client.error.no_installed_profile",
+            version = minecraft_version,
+            loader = loader_type.get_localized_name()
+        )));
+    }
+    let names = installed
+        .iter()
+        .map(|(name, _, _)| name.as_str())
+        .collect::<Vec<_>>();
+
+    let records = names
+        .iter()
+        .filter_map(|name| {
+            let record = std::fs::read(versions_dir.join(name).join(INSTALL_RECORD)).ok()?;
+            serde_json::from_slice::<InstallResult>(&record).ok()
+        })
+        .collect::<Vec<_>>();
+
+    let mut profiles = Vec::new();
+    if let Ok(file) = get_launcher_profiles_json(location.clone()) {
+        let json = serde_json::from_slice::<Value>(&std::fs::read(file)?)?;
+        let recorded = records
+            .iter()
+            .filter_map(|record| match &record.profile {
+                Some(ProfileChange::Added(key) | ProfileChange::Updated(key)) => Some(key),
+                None => None,
+            })
+            .collect::<Vec<_>>();
+        for (key, profile) in json["profiles"].as_object().into_iter().flatten() {
+            if recorded.contains(&key)
+                || profile["lastVersionId"]
+                    .as_str()
+                    .is_some_and(|version| names.contains(&version))
+            {
+                profiles.push(key.clone());
+            }
+        }
+    }
+
+    let mut directories = names
+        .iter()
+        .map(|name| versions_dir.join(name))
+        .collect::<Vec<_>>();
+    let still_inherited = inherited_versions(&versions_dir, &names)?;
+    for vanilla in records
+        .iter()
+        .flat_map(|record| &record.created)
+        .filter_map(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+    {
+        let dir = versions_dir.join(&vanilla);
+        if !names.contains(&vanilla.as_str())
+            && !still_inherited.contains(&vanilla)
+            && !directories.contains(&dir)
+            && dir.exists()
+        {
+            directories.push(dir);
+        }
+    }
+
+    Ok(UninstallPlan {
+        directories,
+        profiles,
+        location,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn inherited_versions(
+    versions_dir: &Path,
+    excluded: &[&str],
+) -> Result<Vec<String>, InstallerError> {
+    let mut inherited = Vec::new();
+    for entry in std::fs::read_dir(versions_dir)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if excluded.contains(&name.as_str()) {
+            continue;
+        }
+        if let Ok(json) = read_profile_json(&versions_dir.join(&name).join(name.clone() + ".json"))
+            && let Some(parent) = json["inheritsFrom"].as_str()
+        {
+            inherited.push(parent.to_owned());
+        }
     }
+    Ok(inherited)
+}
 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn uninstall(
+    sender: UnboundedSender<(f32, String)>,
+    plan: UninstallPlan,
+) -> Result<(), InstallerError> {
+    for dir in &plan.directories {
+        let _ = sender.send((0.5, t!("client.info.removing", path = dir.display()).into()));
+        std::fs::remove_dir_all(super::long_path(dir))?;
+    }
+    if !plan.profiles.is_empty() {
+        let _guard = PROFILES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let file = get_launcher_profiles_json(plan.location)?;
+        let mut json = serde_json::from_slice::<Value>(&std::fs::read(&file)?)?;
+        if let Some(profiles) = json["profiles"].as_object_mut() {
+            for profile in &plan.profiles {
+                let _ = sender.send((
+                    0.9,
+                    t!("client.info.removing_profile", profile = profile).into(),
+                ));
+                profiles.remove(profile);
+            }
+        }
+        std::fs::write(&file, super::to_json(&json)?)?;
+    }
     let _ = sender.send((1.0, t!("client.info.done").into()));
+    Ok(())
+}
 
+/// If several match, the most recently modified one is used.
+#[cfg(not(target_arch = "wasm32"))]
+fn find_installed_profile(
+    versions_dir: &Path,
+    minecraft_version: &str,
+    loader_type: &LoaderType,
+    generation: Option<u32>,
+) -> Result<Option<(String, u32)>, InstallerError> {
+    Ok(
+        find_installed_profiles(versions_dir, minecraft_version, loader_type, generation)?
+            .into_iter()
+            .max_by_key(|(_, _, modified)| *modified)
+            .map(|(name, calamus_gen, _)| (name, calamus_gen)),
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn find_exact_install(
+    location: &Path,
+    minecraft_version: &str,
+    loader_type: &LoaderType,
+    loader_version: &str,
+) -> Option<(String, u32)> {
+    let versions_dir = location.join("versions");
+    let loader_library = loader_type.library_prefix() + loader_version;
+    find_installed_profiles(&versions_dir, minecraft_version, loader_type, None)
+        .ok()?
+        .into_iter()
+        .filter(|(name, calamus_gen, _)| {
+            let vanilla = format!("{minecraft_version}-gen{calamus_gen}");
+            versions_dir
+                .join(&vanilla)
+                .join(vanilla.clone() + ".json")
+                .exists()
+                && read_profile_json(&versions_dir.join(name).join(name.clone() + ".json"))
+                    .is_ok_and(|json| {
+                        json["libraries"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|library| library["name"].as_str())
+                            .any(|library| library == loader_library)
+                    })
+        })
+        .max_by_key(|(_, _, modified)| *modified)
+        .map(|(name, calamus_gen, _)| (name, calamus_gen))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn find_installed_profiles(
+    versions_dir: &Path,
+    minecraft_version: &str,
+    loader_type: &LoaderType,
+    generation: Option<u32>,
+) -> Result<Vec<(String, u32, std::time::SystemTime)>, InstallerError> {
+    let mut found = Vec::new();
+    if !versions_dir.exists() {
+        return Ok(found);
+    }
+    let loader_library = loader_type.library_prefix();
+    let inherits_prefix = format!("{minecraft_version}-gen");
+
+    for entry in std::fs::read_dir(versions_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let file = entry.path().join(name.clone() + ".json");
+        let Ok(contents) = std::fs::read(&file) else {
+            continue;
+        };
+        let Ok(json) = serde_json::from_slice::<Value>(&contents) else {
+            continue;
+        };
+        let Some(calamus_gen) = json["inheritsFrom"]
+            .as_str()
+            .and_then(|parent| parent.strip_prefix(&inherits_prefix))
+            .and_then(|generation| generation.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if generation.is_some_and(|g| g != calamus_gen) {
+            continue;
+        }
+        let has_loader = json["libraries"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|library| library["name"].as_str())
+            .any(|library| library.starts_with(&loader_library));
+        if !has_loader {
+            continue;
+        }
+        let modified = std::fs::metadata(&file)?.modified()?;
+        found.push((name, calamus_gen, modified));
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(found)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn prepare_portable_root(
+    sender: &UnboundedSender<(f32, String)>,
+    location: &Path,
+    create_profile: bool,
+) -> Result<(), InstallerError> {
+    std::fs::create_dir_all(location.join("versions"))?;
+    if create_profile && Launcher::detect(location).is_none() {
+        let file = create_profiles_file(location)?;
+        let _ = sender.send((
+            0.1,
+            t!(
+                "client.info.created_portable_profiles",
+                file = file.display()
+            )
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Unlike a portable root nothing else of the launcher layout is set up.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn create_game_dir(
+    sender: &UnboundedSender<(f32, String)>,
+    location: &Path,
+    create_profile: bool,
+) -> Result<(), InstallerError> {
+    if location.exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(location)?;
+    let _ = sender.send((
+        0.1,
+        t!("client.info.created_game_dir", dir = location.display()).into(),
+    ));
+    if create_profile {
+        let file = create_profiles_file(location)?;
+        let _ = sender.send((
+            0.1,
+            t!("client.info.created_profiles", file = file.display()).into(),
+        ));
+    }
     Ok(())
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn create_profiles_file(location: &Path) -> Result<PathBuf, InstallerError> {
+    let file = location.join(Launcher::Java.profiles_file_name());
+    std::fs::write(
+        &file,
+        super::text_file(&serde_json::to_string_pretty(&json!({
+            "profiles": {},
+            "settings": {},
+            "version": 3
+        }))?),
+    )?;
+    Ok(file)
+}
+
 fn get_launcher_profiles_json(game_dir: PathBuf) -> Result<PathBuf, InstallerError> {
-    let launcher_profiles_msstore = game_dir.join("launcher_profiles_microsoft_store.json");
-    if launcher_profiles_msstore.exists() {
-        return Ok(launcher_profiles_msstore);
+    Launcher::detect(&game_dir)
+        .map(|launcher| game_dir.join(launcher.profiles_file_name()))
+        .ok_or(InstallerError::from(t!(
+            "client.error.could_not_find_launcher_profiles_json"
+        )))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn check_game_dir(location: &Path) -> Result<(), InstallerError> {
+    if !location.is_dir() {
+        return Err(InstallerError::from(t!(
+            "client.error.dir_is_a_file",
+            dir = location.display()
+        )));
     }
-    let launcher_profiles = game_dir.join("launcher_profiles.json");
-    if launcher_profiles.exists() {
-        return Ok(launcher_profiles);
+    let not_writable = |e: std::io::Error| {
+        InstallerError::from(t!(
+            "client.error.dir_not_writable",
+            dir = location.display(),
+            error = e
+        ))
+    };
+    let versions_dir = super::long_path(&location.join("versions"));
+    std::fs::create_dir_all(&versions_dir).map_err(not_writable)?;
+    let probe = versions_dir.join(".ornithe-installer-write-test");
+    std::fs::write(&probe, []).map_err(not_writable)?;
+    let _ = std::fs::remove_file(probe);
+    Ok(())
+}
+
+/// Launchers ignore it, it is only there for support.
+const INSTALLER_VERSION_KEY: &str = "ornitheInstallerVersion";
+
+// Several installs may run at once, only one of them may rewrite the profiles file at a time
+static PROFILES_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+static REPAIR_PROFILES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_repair_profiles(repair: bool) {
+    REPAIR_PROFILES.store(repair, Ordering::Relaxed);
+}
+
+static SELECT_PROFILE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_select_profile(select: bool) {
+    SELECT_PROFILE.store(select, Ordering::Relaxed);
+}
+
+/// Older launchers read the top level `selectedProfile` (or `selectedProfileId`), the
+/// current one opens the profile that was used last, so both are updated.
+fn select_profile(json: &mut Value, key: &str) {
+    if let Some(profile) = json["profiles"][key].as_object_mut() {
+        profile.insert("lastUsed".to_owned(), json!(Utc::now()));
+    }
+    let Some(root) = json.as_object_mut() else {
+        return;
+    };
+    let mut selected = false;
+    for selection_key in ["selectedProfile", "selectedProfileId"] {
+        if root.contains_key(selection_key) {
+            root.insert(selection_key.to_owned(), Value::from(key));
+            selected = true;
+        }
+    }
+    if !selected {
+        root.insert("selectedProfile".to_owned(), Value::from(key));
     }
-    Err(InstallerError::from(t!(
-        "client.error.could_not_find_launcher_profiles_json"
-    )))
+}
+
+fn ensure_profiles_object(json: &mut Value, repair: bool) -> Result<bool, InstallerError> {
+    let fn_json_error = || InstallerError::from(t!("client.error.invalid_launcher_profiles_json"));
+    let raw_profiles = json
+        .as_object_mut()
+        .ok_or_else(fn_json_error)?
+        .get_mut("profiles")
+        .ok_or_else(fn_json_error)?;
+    if raw_profiles.is_object() {
+        return Ok(false);
+    }
+    if !repair {
+        return Err(InstallerError::from(t!(
+            "client.error.profiles_not_an_object"
+        )));
+    }
+    *raw_profiles = json!({});
+    Ok(true)
 }
 
 fn update_profiles(
+    sender: &UnboundedSender<(f32, String)>,
     game_dir: PathBuf,
     name: String,
-    version: MinecraftVersion,
+    minecraft_version: &str,
     loader_type: LoaderType,
     calamus_gen: u32,
-) -> Result<(), InstallerError> {
+) -> Result<ProfileChange, InstallerError> {
+    let _guard = PROFILES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
     let launcher_profiles_path = get_launcher_profiles_json(game_dir)?;
 
     let fn_json_error = || InstallerError::from(t!("client.error.invalid_launcher_profiles_json"));
@@ -188,25 +851,29 @@ fn update_profiles(
     match std::fs::read_to_string(launcher_profiles_path.clone()) {
         Ok(launcher_profiles) => match serde_json::from_str::<Value>(&launcher_profiles) {
             Ok(mut json) => {
-                let raw_profiles = json
-                    .as_object_mut()
-                    .ok_or_else(fn_json_error)?
-                    .get_mut("profiles")
-                    .ok_or_else(fn_json_error)?;
-                if !raw_profiles.is_object() {
-                    return Err(InstallerError::from(t!(
-                        "client.error.profiles_not_an_object"
-                    )));
+                if ensure_profiles_object(&mut json, REPAIR_PROFILES.load(Ordering::Relaxed))? {
+                    let mut backup = launcher_profiles_path.clone().into_os_string();
+                    backup.push(format!(".{}.bak", Utc::now().format("%Y%m%d-%H%M%S")));
+                    std::fs::write(&backup, &launcher_profiles)?;
+                    let _ = sender.send((
+                        0.9,
+                        t!(
+                            "client.info.repaired_profiles",
+                            file = launcher_profiles_path.display(),
+                            backup = PathBuf::from(backup).display()
+                        )
+                        .into(),
+                    ));
                 }
-                let profiles = raw_profiles.as_object_mut().ok_or_else(fn_json_error)?;
+                let profiles = json["profiles"].as_object_mut().ok_or_else(fn_json_error)?;
 
                 let new_profile_name = format!(
                     "Ornithe Gen{calamus_gen} {} {}",
                     loader_type.get_localized_name(),
-                    version.id
+                    minecraft_version
                 );
 
-                if profiles.contains_key(&new_profile_name) {
+                let change = if profiles.contains_key(&new_profile_name) {
                     let raw_profile = profiles
                         .get_mut(&new_profile_name)
                         .ok_or_else(fn_json_error)?;
@@ -217,10 +884,13 @@ fn update_profiles(
                         )));
                     }
 
-                    raw_profile
-                        .as_object_mut()
-                        .ok_or_else(fn_json_error)?
-                        .insert("lastVersionId".to_string(), Value::String(name));
+                    let raw_profile = raw_profile.as_object_mut().ok_or_else(fn_json_error)?;
+                    raw_profile.insert("lastVersionId".to_string(), Value::String(name));
+                    raw_profile.insert(
+                        INSTALLER_VERSION_KEY.to_string(),
+                        Value::from(crate::VERSION),
+                    );
+                    ProfileChange::Updated(new_profile_name)
                 } else {
                     let profile = json!({
                         "name": new_profile_name,
@@ -228,14 +898,21 @@ fn update_profiles(
                         "created": Utc::now(),
                         "lastUsed": Utc::now(),
                         "icon": get_icon_string(),
-                        "lastVersionId": name
+                        "lastVersionId": name,
+                        INSTALLER_VERSION_KEY: crate::VERSION
                     });
-                    profiles.insert(new_profile_name, profile);
+                    profiles.insert(new_profile_name.clone(), profile);
+                    ProfileChange::Added(new_profile_name)
+                };
+
+                if SELECT_PROFILE.load(Ordering::Relaxed) {
+                    let (ProfileChange::Added(key) | ProfileChange::Updated(key)) = &change;
+                    select_profile(&mut json, key);
                 }
 
-                std::fs::write(&launcher_profiles_path, serde_json::to_string(&json)?)?;
+                std::fs::write(&launcher_profiles_path, super::to_json(&json)?)?;
 
-                Ok(())
+                Ok(change)
             }
             Err(_) => Err(InstallerError::from(t!(
                 "client.error.failed_to_parse_launcher_profiles_json"
@@ -251,3 +928,147 @@ fn get_icon_string() -> String {
     let base64 = BASE64_STANDARD_NO_PAD.encode(crate::ORNITHE_ICON_BYTES);
     "data:image/png;base64,".to_string() + &base64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repairs_profiles_of_the_wrong_type() {
+        let malformed = r#"{"profiles": [{"name": "Latest release"}], "version": 3}"#;
+
+        let mut json = serde_json::from_str::<Value>(malformed).unwrap();
+        assert!(ensure_profiles_object(&mut json, false).is_err());
+
+        let mut json = serde_json::from_str::<Value>(malformed).unwrap();
+        assert!(ensure_profiles_object(&mut json, true).unwrap());
+        assert_eq!(json["profiles"], json!({}));
+        assert_eq!(json["version"], 3);
+
+        let mut json = json!({"profiles": {"a": {"name": "a"}}});
+        assert!(!ensure_profiles_object(&mut json, true).unwrap());
+        assert_eq!(json["profiles"]["a"]["name"], "a");
+    }
+
+    #[test]
+    fn selects_profile_with_the_launchers_key() {
+        let mut json = json!({"profiles": {"a": {"name": "a"}}});
+        select_profile(&mut json, "a");
+        assert_eq!(json["selectedProfile"], "a");
+        assert!(json["profiles"]["a"]["lastUsed"].is_string());
+
+        let mut json = json!({"profiles": {"a": {}}, "selectedProfileId": "b"});
+        select_profile(&mut json, "a");
+        assert_eq!(json["selectedProfileId"], "a");
+        assert!(json.get("selectedProfile").is_none());
+    }
+
+    fn installed_profile(name: &str, libraries: &[&str], with_vanilla: bool) -> PathBuf {
+        let location = std::env::temp_dir().join(format!(
+            "ornithe-installer-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&location);
+        let write = |profile: &str, json: Value| {
+            let dir = location.join("versions").join(profile);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(profile.to_owned() + ".json"), json.to_string()).unwrap();
+        };
+        write(
+            "fabric-loader-1.8.9",
+            json!({
+                "id": "fabric-loader-1.8.9",
+                "inheritsFrom": "1.8.9-gen2",
+                "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+                "libraries": libraries.iter().map(|name| json!({"name": name})).collect::<Vec<_>>()
+            }),
+        );
+        if with_vanilla {
+            write("1.8.9-gen2", json!({"id": "1.8.9-gen2"}));
+        }
+        location
+    }
+
+    const LOADER: &str = "net.fabricmc:fabric-loader:0.16.14";
+    const INTERMEDIARY: &str = "net.ornithemc:calamus-intermediary:1.8.9:v2";
+
+    #[test]
+    fn verifies_a_complete_profile() {
+        let location = installed_profile("complete", &[LOADER, INTERMEDIARY], true);
+        let (profile, problems) =
+            verify_profile(&location, "1.8.9", LoaderType::Fabric, None).unwrap();
+        assert_eq!(profile, "fabric-loader-1.8.9");
+        assert!(problems.is_empty(), "{problems:?}");
+        let _ = std::fs::remove_dir_all(location);
+    }
+
+    #[test]
+    fn reports_a_missing_vanilla_parent() {
+        let location = installed_profile("no-parent", &[LOADER, INTERMEDIARY], false);
+        let (_, problems) = verify_profile(&location, "1.8.9", LoaderType::Fabric, None).unwrap();
+        assert_eq!(
+            problems,
+            [String::from(t!(
+                "client.verify.missing_vanilla_profile",
+                profile = "1.8.9-gen2"
+            ))]
+        );
+        let _ = std::fs::remove_dir_all(location);
+    }
+
+    #[test]
+    fn reports_a_missing_intermediary_library() {
+        let location = installed_profile("no-intermediary", &[LOADER], true);
+        let (_, problems) = verify_profile(&location, "1.8.9", LoaderType::Fabric, None).unwrap();
+        assert_eq!(
+            problems,
+            [String::from(t!(
+                "client.verify.missing_library",
+                library = "net.ornithemc:calamus-intermediary"
+            ))]
+        );
+        let _ = std::fs::remove_dir_all(location);
+    }
+
+    #[test]
+    fn uninstall_removes_the_vanilla_version_it_created() {
+        let location = installed_profile("uninstall", &[LOADER, INTERMEDIARY], true);
+        let versions_dir = location.join("versions");
+        let record = InstallResult {
+            created: vec![
+                versions_dir.join("1.8.9-gen2"),
+                versions_dir.join("fabric-loader-1.8.9"),
+            ],
+            ..Default::default()
+        };
+        std::fs::write(
+            versions_dir
+                .join("fabric-loader-1.8.9")
+                .join(INSTALL_RECORD),
+            serde_json::to_vec(&record).unwrap(),
+        )
+        .unwrap();
+        let plan = || plan_uninstall(location.clone(), "1.8.9", LoaderType::Fabric, None).unwrap();
+        assert_eq!(
+            plan().directories,
+            [
+                versions_dir.join("fabric-loader-1.8.9"),
+                versions_dir.join("1.8.9-gen2")
+            ]
+        );
+
+        // Another version still needing the vanilla profile keeps it installed
+        let other = versions_dir.join("quilt-loader-1.8.9");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(
+            other.join("quilt-loader-1.8.9.json"),
+            json!({"inheritsFrom": "1.8.9-gen2"}).to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            plan().directories,
+            [versions_dir.join("fabric-loader-1.8.9")]
+        );
+        let _ = std::fs::remove_dir_all(location);
+    }
+}
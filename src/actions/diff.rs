@@ -0,0 +1,208 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde_json::Value;
+
+use crate::errors::InstallerError;
+
+pub struct ProfileDiff {
+    pub profile_a: String,
+    pub profile_b: String,
+    pub sections: Vec<(String, Vec<String>)>,
+}
+
+impl ProfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
+
+impl std::fmt::Display for ProfileDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "--- {}\n+++ {}", self.profile_a, self.profile_b)?;
+        if self.is_empty() {
+            return writeln!(f, "\nThe profiles do not differ.");
+        }
+        for (name, lines) in &self.sections {
+            writeln!(f, "\n{name}:")?;
+            for line in lines {
+                writeln!(f, "{line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn diff_profiles(
+    location: &Path,
+    profile_a: &str,
+    profile_b: &str,
+) -> Result<ProfileDiff, InstallerError> {
+    let a = read_profile(location, profile_a)?;
+    let b = read_profile(location, profile_b)?;
+    Ok(compare(profile_a, &a, profile_b, &b))
+}
+
+fn compare(profile_a: &str, a: &Value, profile_b: &str, b: &Value) -> ProfileDiff {
+    let mut diff = ProfileDiff {
+        profile_a: profile_a.to_owned(),
+        profile_b: profile_b.to_owned(),
+        sections: Vec::new(),
+    };
+
+    for key in ["inheritsFrom", "mainClass"] {
+        let (value_a, value_b) = (a[key].as_str(), b[key].as_str());
+        if value_a != value_b {
+            add_section(
+                &mut diff,
+                key,
+                vec![
+                    format!("- {}", value_a.unwrap_or("<none>")),
+                    format!("+ {}", value_b.unwrap_or("<none>")),
+                ],
+            );
+        }
+    }
+
+    let libraries_a = libraries(a);
+    let libraries_b = libraries(b);
+    let mut library_lines = Vec::new();
+    for (artifact, version_a) in &libraries_a {
+        match libraries_b.get(artifact) {
+            None => library_lines.push(format!("- {artifact}:{version_a}")),
+            Some(version_b) if version_a != version_b => {
+                library_lines.push(format!("~ {artifact}: {version_a} -> {version_b}"))
+            }
+            _ => {}
+        }
+    }
+    for (artifact, version_b) in &libraries_b {
+        if !libraries_a.contains_key(artifact) {
+            library_lines.push(format!("+ {artifact}:{version_b}"));
+        }
+    }
+    add_section(&mut diff, "libraries", library_lines);
+
+    for (name, side) in [("game arguments", "game"), ("jvm arguments", "jvm")] {
+        let arguments_a = arguments(a, side);
+        let arguments_b = arguments(b, side);
+        let mut lines = Vec::new();
+        for argument in &arguments_a {
+            if !arguments_b.contains(argument) {
+                lines.push(format!("- {argument}"));
+            }
+        }
+        for argument in &arguments_b {
+            if !arguments_a.contains(argument) {
+                lines.push(format!("+ {argument}"));
+            }
+        }
+        add_section(&mut diff, name, lines);
+    }
+
+    diff
+}
+
+fn read_profile(location: &Path, profile: &str) -> Result<Value, InstallerError> {
+    let file = location
+        .join("versions")
+        .join(profile)
+        .join(profile.to_owned() + ".json");
+    if !file.exists() {
+        return Err(InstallerError::from(t!(
+            "diff.error.profile_not_found",
+            profile = profile,
+            file = file.display()
+        )));
+    }
+    Ok(serde_json::from_slice(&std::fs::read(file)?)?)
+}
+
+fn libraries(profile: &Value) -> BTreeMap<String, String> {
+    let mut libraries = BTreeMap::new();
+    for library in profile["libraries"].as_array().into_iter().flatten() {
+        let Some(name) = library["name"].as_str() else {
+            continue;
+        };
+        let mut parts = name.split(':').collect::<Vec<_>>();
+        if parts.len() < 3 {
+            libraries.insert(name.to_owned(), String::new());
+            continue;
+        }
+        let version = parts.remove(2);
+        libraries.insert(parts.join(":"), version.to_owned());
+    }
+    libraries
+}
+
+fn arguments(profile: &Value, side: &str) -> Vec<String> {
+    let mut arguments = profile["arguments"][side]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|arg| arg.as_str().map(|s| s.to_owned()))
+        .collect::<Vec<_>>();
+    if side == "game"
+        && let Some(legacy) = profile["minecraftArguments"].as_str()
+    {
+        arguments.extend(legacy.split_whitespace().map(|s| s.to_owned()));
+    }
+    arguments
+}
+
+fn add_section(diff: &mut ProfileDiff, name: &str, lines: Vec<String>) {
+    if !lines.is_empty() {
+        diff.sections.push((name.to_owned(), lines));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn profile(main_class: &str, libraries: &[&str]) -> Value {
+        json!({
+            "inheritsFrom": "1.8.9",
+            "mainClass": main_class,
+            "libraries": libraries.iter().map(|name| json!({"name": name})).collect::<Vec<_>>(),
+            "arguments": {"game": [], "jvm": []}
+        })
+    }
+
+    #[test]
+    fn identical_profiles_do_not_differ() {
+        let a = profile(
+            "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            &["a:b:1"],
+        );
+        let diff = compare("a", &a, "b", &a);
+        assert!(diff.is_empty());
+        assert!(diff.to_string().ends_with("The profiles do not differ.\n"));
+    }
+
+    #[test]
+    fn reports_changed_libraries_and_main_class() {
+        let a = profile("Main", &["a:b:1", "c:d:1"]);
+        let b = profile("Other", &["a:b:2", "e:f:1"]);
+        let diff = compare("a", &a, "b", &b);
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.sections,
+            [
+                (
+                    "mainClass".to_owned(),
+                    vec!["- Main".to_owned(), "+ Other".to_owned()]
+                ),
+                (
+                    "libraries".to_owned(),
+                    vec![
+                        "~ a:b: 1 -> 2".to_owned(),
+                        "- c:d:1".to_owned(),
+                        "+ e:f:1".to_owned()
+                    ]
+                ),
+            ]
+        );
+    }
+}
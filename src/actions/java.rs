@@ -0,0 +1,173 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::errors::InstallerError;
+
+#[cfg(windows)]
+const JAVA_BINARY: &str = "java.exe";
+#[cfg(not(windows))]
+const JAVA_BINARY: &str = "java";
+
+#[derive(Debug, Clone)]
+pub struct JavaInstallation {
+    pub path: PathBuf,
+    pub major_version: u32,
+}
+
+pub fn find_installations() -> Vec<JavaInstallation> {
+    let mut candidates = Vec::new();
+    if let Some(home) = std::env::var_os("JAVA_HOME") {
+        candidates.push(PathBuf::from(home).join("bin").join(JAVA_BINARY));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        candidates.extend(std::env::split_paths(&path).map(|dir| dir.join(JAVA_BINARY)));
+    }
+    for root in install_roots() {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let home = entry.path();
+            candidates.push(home.join("bin").join(JAVA_BINARY));
+            candidates.push(home.join("Contents/Home/bin").join(JAVA_BINARY));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut installations = Vec::new();
+    for candidate in candidates {
+        let Ok(path) = candidate.canonicalize() else {
+            continue;
+        };
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        if let Some(major_version) = probe_major_version(&path) {
+            installations.push(JavaInstallation {
+                path,
+                major_version,
+            });
+        }
+    }
+    installations.sort_by_key(|java| java.major_version);
+    installations
+}
+
+fn install_roots() -> Vec<PathBuf> {
+    if cfg!(windows) {
+        ["ProgramFiles", "ProgramFiles(x86)"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .flat_map(|dir| {
+                let dir = PathBuf::from(dir);
+                [
+                    dir.join("Java"),
+                    dir.join("Eclipse Adoptium"),
+                    dir.join("Microsoft"),
+                    dir.join("Zulu"),
+                ]
+            })
+            .collect()
+    } else if cfg!(target_os = "macos") {
+        vec![PathBuf::from("/Library/Java/JavaVirtualMachines")]
+    } else {
+        vec![PathBuf::from("/usr/lib/jvm"), PathBuf::from("/opt")]
+    }
+}
+
+fn probe_major_version(java: &Path) -> Option<u32> {
+    // `java -version` prints to stderr
+    let output = Command::new(java).arg("-version").output().ok()?;
+    parse_major_version(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// Java 8 and older report themselves as `1.8.0_xxx`. With `JAVA_TOOL_OPTIONS` set,
+/// a notice comes before the version line.
+fn parse_major_version(output: &str) -> Option<u32> {
+    let version = output
+        .lines()
+        .find(|line| line.contains("version \""))?
+        .split('"')
+        .nth(1)?;
+    let mut parts = version.split(['.', '-', '_', '+']);
+    match parts.next()?.parse::<u32>().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// Picks the installation matching `required` exactly, or else the oldest newer one,
+/// as old Minecraft versions tend to break on much newer Java releases.
+pub fn select_compatible(
+    installations: &[JavaInstallation],
+    required: u32,
+) -> Result<&JavaInstallation, InstallerError> {
+    installations
+        .iter()
+        .find(|java| java.major_version == required)
+        .or_else(|| {
+            installations
+                .iter()
+                .find(|java| java.major_version > required)
+        })
+        .ok_or(InstallerError::from(t!(
+            "java.error.no_compatible_java",
+            required = required
+        )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_java_versions() {
+        assert_eq!(
+            parse_major_version("openjdk version \"1.8.0_392\"\nOpenJDK Runtime Environment"),
+            Some(8)
+        );
+        assert_eq!(
+            parse_major_version("openjdk version \"17.0.9\" 2023-10-17"),
+            Some(17)
+        );
+        assert_eq!(
+            parse_major_version("java version \"21\" 2023-09-19"),
+            Some(21)
+        );
+        assert_eq!(parse_major_version("not java"), None);
+    }
+
+    #[test]
+    fn skips_picked_up_options_notice() {
+        assert_eq!(
+            parse_major_version(
+                "Picked up JAVA_TOOL_OPTIONS: -Dfile.encoding=\"UTF-8\"\nopenjdk version \"17.0.9\" 2023-10-17"
+            ),
+            Some(17)
+        );
+        assert_eq!(
+            parse_major_version("Picked up _JAVA_OPTIONS: -Xmx2G\njava version \"1.8.0_392\""),
+            Some(8)
+        );
+    }
+
+    #[test]
+    fn prefers_exact_major_version() {
+        let installations = [8, 17, 21].map(|major_version| JavaInstallation {
+            path: PathBuf::from(format!("java{major_version}")),
+            major_version,
+        });
+        assert_eq!(
+            select_compatible(&installations, 17).unwrap().major_version,
+            17
+        );
+        assert_eq!(
+            select_compatible(&installations, 11).unwrap().major_version,
+            17
+        );
+        assert!(select_compatible(&installations, 22).is_err());
+    }
+}
@@ -1,8 +1,15 @@
 use std::{
     io::{Seek, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
+use serde::Serialize;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
 #[cfg(target_arch = "wasm32")]
 use web_sys::{
     Blob, BlobPropertyBag,
@@ -11,10 +18,178 @@ use web_sys::{
 use zip::{ZipWriter, write::SimpleFileOptions};
 
 use crate::errors::InstallerError;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod assets;
 pub mod client;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod diff;
+#[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
+pub mod java;
+#[cfg(feature = "mmc")]
 pub mod prism_pack;
+#[cfg(feature = "server")]
 pub mod server;
 
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
+}
+
+static PRETTY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_pretty(pretty: bool) {
+    PRETTY.store(pretty, Ordering::Relaxed);
+}
+
+fn to_json(value: &impl Serialize) -> Result<Vec<u8>, InstallerError> {
+    Ok(if PRETTY.load(Ordering::Relaxed) {
+        text_file(&serde_json::to_string_pretty(value)?)
+    } else {
+        serde_json::to_vec(value)?
+    })
+}
+
+static CRLF: AtomicBool = AtomicBool::new(cfg!(windows));
+
+pub fn set_crlf(crlf: bool) {
+    CRLF.store(crlf, Ordering::Relaxed);
+}
+
+fn text_file(text: &str) -> Vec<u8> {
+    convert_line_endings(text, CRLF.load(Ordering::Relaxed)).into_bytes()
+}
+
+fn convert_line_endings(text: &str, crlf: bool) -> String {
+    let lf = text.replace("\r\n", "\n");
+    if crlf { lf.replace('\n', "\r\n") } else { lf }
+}
+
+static DIAGNOSTIC_SUBSCRIBERS: Mutex<Vec<UnboundedSender<Diagnostic>>> = Mutex::new(Vec::new());
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub message: String,
+}
+
+pub fn subscribe_diagnostics() -> UnboundedReceiver<Diagnostic> {
+    let (sender, receiver) = unbounded_channel();
+    if let Ok(mut subscribers) = DIAGNOSTIC_SUBSCRIBERS.lock() {
+        subscribers.push(sender);
+    }
+    receiver
+}
+
+pub fn emit_diagnostic(level: DiagnosticLevel, message: impl Into<String>) {
+    let diagnostic = Diagnostic {
+        level,
+        message: message.into(),
+    };
+    if let Ok(mut subscribers) = DIAGNOSTIC_SUBSCRIBERS.lock() {
+        subscribers.retain(|subscriber| subscriber.send(diagnostic.clone()).is_ok());
+    }
+}
+
+/// Fails the installation instead in strict mode.
+pub fn warn(
+    sender: &UnboundedSender<(f32, String)>,
+    progress: f32,
+    message: impl Into<String>,
+) -> Result<(), InstallerError> {
+    let message = message.into();
+    if STRICT.load(Ordering::Relaxed) {
+        return Err(InstallerError(message));
+    }
+    emit_diagnostic(DiagnosticLevel::Warning, message.clone());
+    let _ = sender.send((progress, message));
+    Ok(())
+}
+
+/// Reports progress in units of completed work (downloaded files, finished requests, ...)
+/// spread over the `start..end` range of the progress bar.
+pub struct ProgressTracker<'a> {
+    sender: &'a UnboundedSender<(f32, String)>,
+    start: f32,
+    end: f32,
+    total: usize,
+    done: usize,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub fn new(
+        sender: &'a UnboundedSender<(f32, String)>,
+        start: f32,
+        end: f32,
+        total: usize,
+    ) -> Self {
+        Self {
+            sender,
+            start,
+            end,
+            total,
+            done: 0,
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return self.end;
+        }
+        let done = self.done.min(self.total) as f32 / self.total as f32;
+        self.start + (self.end - self.start) * done
+    }
+
+    pub fn message(&self, message: impl Into<String>) {
+        let _ = self.sender.send((self.fraction(), message.into()));
+    }
+
+    pub fn advance(&mut self, message: impl Into<String>) {
+        self.done += 1;
+        self.message(message);
+    }
+}
+
+/// Converts a path to the extended-length `\\?\` form on Windows, lifting the MAX_PATH
+/// limit for everything created below it. Paths on other platforms are returned as is.
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::path::{Component, Prefix};
+
+        let Ok(absolute) = std::path::absolute(path) else {
+            return path.to_path_buf();
+        };
+        let mut components = absolute.components();
+        let mut prefixed = std::ffi::OsString::from(r"\\?\");
+        match components.next() {
+            Some(Component::Prefix(prefix)) => match prefix.kind() {
+                Prefix::Disk(_) => prefixed.push(absolute.as_os_str()),
+                Prefix::UNC(server, share) => {
+                    prefixed.push(r"UNC\");
+                    prefixed.push(server);
+                    prefixed.push(r"\");
+                    prefixed.push(share);
+                    prefixed.push(components.as_path().as_os_str());
+                }
+                // Already verbatim or a device path
+                _ => return absolute,
+            },
+            _ => return absolute,
+        }
+        PathBuf::from(prefixed)
+    }
+    #[cfg(not(windows))]
+    path.to_path_buf()
+}
+
 #[cfg(target_arch = "wasm32")]
 pub fn download_file(name: impl Into<String>, buf: &Vec<u8>) {
     let arr = Uint8Array::new_from_slice(buf);
@@ -37,6 +212,14 @@ trait Writer {
     fn write_file(&mut self, path: &str, buf: &[u8]) -> Result<(), InstallerError>;
 
     fn create_dir(&mut self, path: &str) -> Result<(), InstallerError>;
+
+    fn set_modified(
+        &mut self,
+        _path: &str,
+        _time: std::time::SystemTime,
+    ) -> Result<(), InstallerError> {
+        Ok(())
+    }
 }
 
 impl Writer for PathBuf {
@@ -47,6 +230,16 @@ impl Writer for PathBuf {
         Ok(())
     }
 
+    fn set_modified(
+        &mut self,
+        path: &str,
+        time: std::time::SystemTime,
+    ) -> Result<(), InstallerError> {
+        let file = std::fs::File::options().write(true).open(self.join(path))?;
+        file.set_modified(time)?;
+        Ok(())
+    }
+
     fn create_dir(&mut self, path: &str) -> Result<(), InstallerError> {
         let new_file = self.join(path);
         std::fs::create_dir_all(new_file)?;
@@ -67,3 +260,27 @@ where
         Ok(self.add_directory(path, SimpleFileOptions::default())?)
     }
 }
+
+/// Collects the files in memory, so PrismLauncher instances can be generated first and
+/// written out later, e.g. all into one zip with shared components.
+impl Writer for std::collections::HashMap<String, Vec<u8>> {
+    fn write_file(&mut self, path: &str, buf: &[u8]) -> Result<(), InstallerError> {
+        self.insert(path.to_owned(), buf.to_vec());
+        Ok(())
+    }
+
+    fn create_dir(&mut self, _path: &str) -> Result<(), InstallerError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_line_endings() {
+        assert_eq!(convert_line_endings("a\nb\r\n", true), "a\r\nb\r\n");
+        assert_eq!(convert_line_endings("a\nb\r\n", false), "a\nb\n");
+    }
+}
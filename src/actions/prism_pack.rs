@@ -1,10 +1,14 @@
-use std::path::PathBuf;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use serde_json::{Value, json};
 use tokio::sync::mpsc::UnboundedSender;
 use zip::ZipWriter;
 
 use crate::{
+    actions::Writer,
     errors::InstallerError,
     net::{
         GameSide,
@@ -18,6 +22,65 @@ const INTERMEDIARY_PATCH: &str =
     include_str!("../../res/packformat/patches/net.fabricmc.intermediary.json");
 const INSTANCE_CONFIG: &str = include_str!("../../res/packformat/instance.cfg");
 const MMC_PACK: &str = include_str!("../../res/packformat/mmc-pack.json");
+const MMC_PACK_FORMAT_VERSION: u64 = 1;
+
+#[derive(Clone)]
+pub struct ManagedPack {
+    pub id: String,
+    pub pack_type: String,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct WindowSettings {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fullscreen: bool,
+}
+
+impl WindowSettings {
+    fn instance_config(&self) -> String {
+        if self.width.is_none() && self.height.is_none() && !self.fullscreen {
+            return String::new();
+        }
+        let mut config = String::from("\nOverrideWindow=true");
+        if let Some(width) = self.width {
+            config += &format!("\nMinecraftWinWidth={width}");
+        }
+        if let Some(height) = self.height {
+            config += &format!("\nMinecraftWinHeight={height}");
+        }
+        if self.fullscreen {
+            config += "\nLaunchMaximized=true";
+        }
+        config
+    }
+}
+
+/// The patch lists several compatible Java versions, so the launcher may well pick a
+/// newer one, which versions needing Java 8 do not start on.
+fn java_note(required_java: u32) -> String {
+    if required_java != 8 {
+        return String::new();
+    }
+    let note: String = t!("mmc.note.requires_java", java = required_java).into();
+    format!(
+        "\nnotes=\"{}\"",
+        note.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OnExists {
+    Abort,
+    Overwrite,
+    Backup,
+}
+
+pub struct Instance {
+    pub profile_name: String,
+    generation: u32,
+    files: HashMap<String, Vec<u8>>,
+}
 
 pub async fn install(
     sender: UnboundedSender<(f32, String)>,
@@ -30,6 +93,9 @@ pub async fn install(
     generate_zip: bool,
     generation: Option<u32>,
     include_flap: bool,
+    on_exists: OnExists,
+    managed_pack: Option<ManagedPack>,
+    window: WindowSettings,
 ) -> Result<(), InstallerError> {
     let message = if cfg!(target_arch = "wasm32") {
         t!(
@@ -56,7 +122,113 @@ pub async fn install(
     #[cfg(not(target_arch = "wasm32"))]
     let output_dir = output_dir.canonicalize()?;
 
-    let _ = sender.send((0.2, t!("mmc.info.fetching_version_information").into()));
+    let instance = generate(
+        &sender,
+        &version,
+        &intermediary_version,
+        &loader_type,
+        &loader_version,
+        &generation,
+        include_flap,
+        managed_pack,
+        window,
+    )
+    .await?;
+    let profile_name = instance.profile_name.clone();
+
+    let (output_file, instance_dir) = if generate_zip {
+        (output_dir.join(profile_name.clone() + ".zip"), None)
+    } else {
+        let dir = output_dir.join(profile_name.clone());
+        if on_exists == OnExists::Abort && std::fs::exists(&dir).unwrap_or_default() {
+            return Err(InstallerError::from(t!(
+                "mmc.error.instance_already_exists"
+            )));
+        }
+        // The instance is written to a staging directory and only moved into place
+        // once complete, so a failed run never leaves a partial instance behind.
+        let staging = output_dir.join(format!(".{profile_name}.partial"));
+        if std::fs::exists(&staging).unwrap_or_default() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        std::fs::create_dir_all(&staging)?;
+        (staging, Some(dir))
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    let mut buf = std::io::Cursor::new(Vec::new());
+
+    let mut zip: Box<dyn Writer> = if generate_zip {
+        let _ = sender.send((0.75, t!("mmc.info.generating_instance_zip").into()));
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if std::fs::exists(&output_file).unwrap_or_default() {
+                std::fs::remove_file(&output_file)?;
+            }
+            let file = std::fs::File::create_new(&output_file)?;
+            Box::new(ZipWriter::new(file))
+        }
+        #[cfg(target_arch = "wasm32")]
+        Box::new(ZipWriter::new(&mut buf))
+    } else {
+        let _ = sender.send((0.75, t!("mmc.info.generating_output_files").into()));
+
+        Box::new(output_file.clone())
+    };
+
+    write_files(
+        zip.as_mut(),
+        instance
+            .files
+            .iter()
+            .map(|(path, contents)| (path.clone(), contents.as_slice())),
+    )?;
+
+    let output_file = match instance_dir {
+        Some(dir) => {
+            move_instance_into_place(
+                &sender,
+                &output_file,
+                &dir,
+                &output_dir,
+                &profile_name,
+                on_exists,
+            )?;
+            dir
+        }
+        None => output_file,
+    };
+
+    copy_path(&output_file, copy_profile_path)?;
+
+    let _ = sender.send((1.0, t!("mmc.info.done").into()));
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        drop(zip);
+        wasm_bindgen_futures::spawn_local(async move {
+            super::download_file(&(profile_name + ".zip"), &buf.into_inner());
+        });
+    }
+
+    Ok(())
+}
+
+pub async fn generate(
+    sender: &UnboundedSender<(f32, String)>,
+    version: &MinecraftVersion,
+    intermediary_version: &IntermediaryVersion,
+    loader_type: &LoaderType,
+    loader_version: &LoaderVersion,
+    generation: &Option<u32>,
+    include_flap: bool,
+    managed_pack: Option<ManagedPack>,
+    window: WindowSettings,
+) -> Result<Instance, InstallerError> {
+    // Fetching metadata is the slow part, so the bar advances per finished request
+    let mut progress =
+        super::ProgressTracker::new(sender, 0.1, 0.65, 5 + usize::from(generation.is_none()));
+    progress.message(t!("mmc.info.fetching_version_information"));
     let intermediary_maven = intermediary_version
         .maven
         .clone()
@@ -66,20 +238,25 @@ pub async fn install(
         )))?
         .to_owned();
 
-    let (lwjgl_url, lwjgl_version) = manifest::find_lwjgl_url_version(&version).await?;
+    let (lwjgl_url, lwjgl_version) = manifest::find_lwjgl_url_version(version).await?;
+    progress.advance(String::new());
 
     let calamus_gen = match generation {
-        Some(g) => g,
-        None => meta::fetch_intermediary_generations().await?.stable,
+        Some(g) => *g,
+        None => {
+            let stable = meta::fetch_intermediary_generations().await?.stable;
+            progress.advance(String::new());
+            stable
+        }
     };
 
-    let _ = sender.send((0.4, t!("mmc.info.transforming_templates").into()));
+    progress.message(t!("mmc.info.transforming_templates"));
 
     let mut transformed_pack_json = serde_json::from_str::<Value>(
         &transform_pack_json(
-            &version,
-            &loader_type,
-            &loader_version,
+            version,
+            loader_type,
+            loader_version,
             &lwjgl_version,
             &intermediary_version.version,
         )
@@ -87,99 +264,72 @@ pub async fn install(
     )?;
 
     let transformed_intermediary_patch =
-        transform_intermediary_patch(&version, &intermediary_version.version, &intermediary_maven)
+        transform_intermediary_patch(version, &intermediary_version.version, &intermediary_maven)
             .await?;
 
     let (_, ornithe_launch_json) = meta::fetch_launch_json(
         GameSide::Client,
-        &intermediary_version,
-        &loader_type,
-        &loader_version,
-        &generation,
+        intermediary_version,
+        loader_type,
+        loader_version,
+        generation,
     )
     .await?;
-    let minecraft_patch_json =
-        get_mmc_launch_json(&version, &generation, &lwjgl_version, &ornithe_launch_json).await?;
+    progress.advance(String::new());
+    let (minecraft_patch_json, required_java) =
+        get_mmc_launch_json(version, generation, &lwjgl_version, &ornithe_launch_json).await?;
+    progress.advance(String::new());
 
     let profile_name = format!(
         "Ornithe Gen{calamus_gen} {} {}",
         loader_type.get_localized_name(),
         version.id
     );
-    let output_file = if generate_zip {
-        output_dir.join(profile_name.clone() + ".zip")
-    } else {
-        let dir = output_dir.join(profile_name.clone());
-        if std::fs::exists(&dir).unwrap_or_default() {
-            return Err(InstallerError::from(t!(
-                "mmc.error.instance_already_exists"
-            )));
-        }
-        std::fs::create_dir_all(&dir)?;
-        dir
-    };
 
-    let _ = sender.send((0.5, t!("mmc.info.fetching_library_information").into()));
+    progress.message(t!("mmc.info.fetching_library_information"));
 
     let MavenVersion {
         version: flap_version,
         ..
-    } = maven::get_latest_version("flap").await?;
+    } = maven::get_flap_version().await?;
+    progress.advance(String::new());
 
-    let extra_libs = meta::fetch_profile_libraries(&generation, &version.id).await?;
-    let _ = sender.send((
-        0.6,
-        t!(
-            "mmc.info.found_library_upgrades",
-            num_libraries = extra_libs.len()
-        )
-        .into(),
+    let extra_libs = meta::fetch_profile_libraries(generation, &version.id).await?;
+    progress.advance(t!(
+        "mmc.info.found_library_upgrades",
+        num_libraries = crate::format::format_count(extra_libs.len() as u64)
     ));
 
-    #[cfg(target_arch = "wasm32")]
-    let mut buf = std::io::Cursor::new(Vec::new());
-
-    let mut zip: Box<dyn super::Writer> = if generate_zip {
-        let _ = sender.send((0.65, t!("mmc.info.generating_instance_zip").into()));
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            if std::fs::exists(&output_file).unwrap_or_default() {
-                std::fs::remove_file(&output_file)?;
-            }
-            let file = std::fs::File::create_new(&output_file)?;
-            Box::new(ZipWriter::new(file))
-        }
-        #[cfg(target_arch = "wasm32")]
-        Box::new(ZipWriter::new(&mut buf))
-    } else {
-        let _ = sender.send((0.65, t!("mmc.info.generating_output_files").into()));
-
-        Box::new(output_file.clone())
-    };
+    let mut zip: HashMap<String, Vec<u8>> = HashMap::new();
 
-    zip.write_file(
-        "instance.cfg",
-        INSTANCE_CONFIG
-            .replace("${profile_name}", &profile_name)
-            .as_bytes(),
-    )?;
+    // The installer version is only recorded for support, launchers ignore the key
+    let mut instance_config = INSTANCE_CONFIG
+        .replace("${profile_name}", &profile_name)
+        .replace("${installer_version}", crate::VERSION);
+    if let Some(pack) = managed_pack {
+        instance_config += &format!(
+            "\nManagedPack=true\nManagedPackID={}\nManagedPackType={}",
+            pack.id, pack.pack_type
+        );
+    }
+    instance_config += &window.instance_config();
+    instance_config += &java_note(required_java);
+    zip.write_file("instance.cfg", &super::text_file(&instance_config))?;
 
     zip.write_file("ornithe.png", crate::ORNITHE_ICON_BYTES)?;
 
-    zip.create_dir("patches")?;
-
     zip.write_file(
         "patches/net.fabricmc.intermediary.json",
-        transformed_intermediary_patch.as_bytes(),
+        &super::text_file(&transformed_intermediary_patch),
     )?;
 
     zip.write_file(
         "patches/net.minecraft.json",
-        minecraft_patch_json.as_bytes(),
+        &super::text_file(&minecraft_patch_json),
     )?;
 
     let pack_components = transformed_pack_json["components"].as_array_mut().unwrap();
-    let _ = sender.send((0.75, t!("mmc.info.adding_library_components").into()));
+    let _ = sender.send((0.65, t!("mmc.info.adding_library_components").into()));
     for library in extra_libs {
         let mut colons = library
             .name
@@ -217,46 +367,162 @@ pub async fn install(
             };
         zip.write_file(
             &format!("patches/{}.json", &uid),
-            serde_json::to_string(&json!({
+            &super::to_json(&json!({
                 "formatVersion": 1,
                 "name": "LWJGL ".to_string()+&lwjgl_major.to_string(),
                 "type": "release",
                 "uid": &uid,
                 "version": &lwjgl_version
-            }))?
-            .as_bytes(),
+            }))?,
         )?;
     }
 
     if include_flap {
-        zip.write_file(
-            "patches/net.ornithemc.flap.json",
-            serde_json::to_string(&json!({
-                "formatVersion": 1,
-                "name": "Flap",
-                "type": "release",
-                "uid": "net.ornithemc.flap",
-                "version": flap_version,
-                "+agents": [{
-                    "name": format!("net.ornithemc:flap:{}", flap_version),
-                    "url": maven::MAVEN_URL
-                }]
-            }))?
-            .as_bytes(),
-        )?;
-
-        pack_components.push(json!({
-            "cachedName": "Flap",
-            "cachedVersion": flap_version,
-            "uid": "net.ornithemc.flap"
-        }));
+        add_flap_component(&mut zip, pack_components, &flap_version)?;
     }
 
+    validate_pack_json(&transformed_pack_json, &loader_type, include_flap)?;
     zip.write_file(
         "mmc-pack.json",
-        &serde_json::to_vec_pretty(&transformed_pack_json)?,
+        &super::text_file(&serde_json::to_string_pretty(&transformed_pack_json)?),
     )?;
 
+    Ok(Instance {
+        profile_name,
+        generation: calamus_gen,
+        files: zip,
+    })
+}
+
+/// Writes several instances into one zip in which each distinct patch is stored only once,
+/// under `components/<uid>/<hash>.json`. Every instance directory in the zip has a
+/// `components.json` mapping its `patches/` files to the shared components to copy there.
+pub fn write_shared(
+    sender: &UnboundedSender<(f32, String)>,
+    instances: &[Instance],
+    loader_type: &LoaderType,
+    output_dir: &Path,
+    copy_profile_path: bool,
+) -> Result<(), InstallerError> {
+    let Some(first) = instances.first() else {
+        return Ok(());
+    };
+    let name = format!(
+        "Ornithe Gen{} {} shared components",
+        first.generation,
+        loader_type.get_localized_name()
+    );
+    let files = share_patches(instances)?;
+    let _ = sender.send((
+        0.75,
+        t!(
+            "mmc.info.writing_shared_components",
+            num_instances = crate::format::format_count(instances.len() as u64),
+            num_components = crate::format::format_count(
+                files
+                    .keys()
+                    .filter(|path| path.starts_with("components/"))
+                    .count() as u64
+            )
+        )
+        .into(),
+    ));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let output_file = {
+        std::fs::create_dir_all(output_dir)?;
+        let output_file = output_dir.canonicalize()?.join(name + ".zip");
+        if std::fs::exists(&output_file).unwrap_or_default() {
+            std::fs::remove_file(&output_file)?;
+        }
+        output_file
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut zip = ZipWriter::new(std::fs::File::create_new(&output_file)?);
+    #[cfg(target_arch = "wasm32")]
+    let mut zip = {
+        let _ = output_dir;
+        ZipWriter::new(std::io::Cursor::new(Vec::new()))
+    };
+
+    write_files(
+        &mut zip,
+        files
+            .iter()
+            .map(|(path, contents)| (path.clone(), contents.as_slice())),
+    )?;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        zip.finish()?;
+        copy_path(&output_file, copy_profile_path)?;
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = copy_profile_path;
+        let buf = zip.finish()?.into_inner();
+        wasm_bindgen_futures::spawn_local(async move {
+            super::download_file(&(name + ".zip"), &buf);
+        });
+    }
+
+    let _ = sender.send((1.0, t!("mmc.info.done").into()));
+    Ok(())
+}
+
+fn share_patches(instances: &[Instance]) -> Result<HashMap<String, Vec<u8>>, InstallerError> {
+    let mut files = HashMap::new();
+    for instance in instances {
+        let mut paths = instance.files.keys().collect::<Vec<_>>();
+        paths.sort();
+        let mut components = serde_json::Map::new();
+        for path in paths {
+            let contents = &instance.files[path];
+            match path
+                .strip_prefix("patches/")
+                .and_then(|file| file.strip_suffix(".json"))
+            {
+                Some(uid) => {
+                    let hash = crate::net::sha1(contents);
+                    let component = format!("components/{uid}/{}.json", &hash[..12]);
+                    components.insert(path.clone(), Value::from(component.clone()));
+                    files.insert(component, contents.clone());
+                }
+                None => {
+                    files.insert(
+                        format!("{}/{path}", instance.profile_name),
+                        contents.clone(),
+                    );
+                }
+            }
+        }
+        files.insert(
+            format!("{}/components.json", instance.profile_name),
+            super::to_json(&components)?,
+        );
+    }
+    Ok(files)
+}
+
+fn write_files(
+    zip: &mut dyn Writer,
+    files: impl IntoIterator<Item = (String, &[u8])>,
+) -> Result<(), InstallerError> {
+    let mut files = files.into_iter().collect::<Vec<_>>();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut dirs = HashSet::new();
+    for (path, contents) in files {
+        if let Some((dir, _)) = path.rsplit_once('/')
+            && dirs.insert(dir.to_owned())
+        {
+            zip.create_dir(dir)?;
+        }
+        zip.write_file(&path, contents)?;
+    }
+    Ok(())
+}
+
+fn copy_path(path: &Path, copy_profile_path: bool) -> Result<(), InstallerError> {
     #[cfg(all(
         any(unix, windows),
         not(any(target_os = "android", target_arch = "wasm32"))
@@ -264,7 +530,7 @@ pub async fn install(
     {
         if copy_profile_path {
             arboard::Clipboard::new()
-                .and_then(|mut cp| cp.set().text(output_file.to_string_lossy().into_owned()))
+                .and_then(|mut cp| cp.set().text(path.to_string_lossy().into_owned()))
                 .map_err(|_| InstallerError::from(t!("mmc.error.failed_to_copy_path")))?;
         }
     }
@@ -273,22 +539,135 @@ pub async fn install(
         any(target_os = "android", target_arch = "wasm32")
     ))]
     {
-        let _ = copy_profile_path;
+        let _ = (path, copy_profile_path);
     }
+    Ok(())
+}
 
-    let _ = sender.send((1.0, t!("mmc.info.done").into()));
-
-    #[cfg(target_arch = "wasm32")]
-    {
-        drop(zip);
-        wasm_bindgen_futures::spawn_local(async move {
-            super::download_file(&(profile_name + ".zip"), &buf.into_inner());
-        });
+fn move_instance_into_place(
+    sender: &UnboundedSender<(f32, String)>,
+    staging: &Path,
+    dir: &Path,
+    output_dir: &Path,
+    profile_name: &str,
+    on_exists: OnExists,
+) -> Result<(), InstallerError> {
+    if std::fs::exists(dir).unwrap_or_default() {
+        match on_exists {
+            OnExists::Abort => {
+                return Err(InstallerError::from(t!(
+                    "mmc.error.instance_already_exists"
+                )));
+            }
+            OnExists::Overwrite => {
+                let _ = sender.send((
+                    0.95,
+                    t!("mmc.info.overwriting_instance", instance = profile_name).into(),
+                ));
+                std::fs::remove_dir_all(dir)?;
+            }
+            OnExists::Backup => {
+                // Prism lists every directory in its instances folder with an instance.cfg,
+                // so backups go one level deeper where it doesn't look
+                let backups = output_dir.join(".ornithe-backups");
+                std::fs::create_dir_all(&backups)?;
+                let backup = backups.join(format!(
+                    "{profile_name}.{}",
+                    chrono::Local::now().format("%Y%m%d-%H%M%S")
+                ));
+                let _ = sender.send((
+                    0.95,
+                    t!(
+                        "mmc.info.backing_up_instance",
+                        instance = profile_name,
+                        backup = backup.display()
+                    )
+                    .into(),
+                ));
+                std::fs::rename(dir, &backup)?;
+            }
+        }
     }
+    std::fs::rename(staging, dir)?;
+    Ok(())
+}
 
+fn add_flap_component(
+    zip: &mut dyn Writer,
+    pack_components: &mut Vec<Value>,
+    flap_version: &str,
+) -> Result<(), InstallerError> {
+    zip.write_file(
+        "patches/net.ornithemc.flap.json",
+        &super::to_json(&json!({
+            "formatVersion": 1,
+            "name": "Flap",
+            "type": "release",
+            "uid": "net.ornithemc.flap",
+            "version": flap_version,
+            "+agents": [{
+                "name": format!("net.ornithemc:flap:{}", flap_version),
+                "url": maven::MAVEN_URL
+            }]
+        }))?,
+    )?;
+
+    pack_components.push(json!({
+        "cachedName": "Flap",
+        "cachedVersion": flap_version,
+        "uid": "net.ornithemc.flap"
+    }));
     Ok(())
 }
 
+fn validate_pack_json(
+    pack: &Value,
+    loader_type: &LoaderType,
+    include_flap: bool,
+) -> Result<(), InstallerError> {
+    let invalid =
+        |reason: &str| InstallerError::from(t!("mmc.error.invalid_pack_json", reason = reason));
+    if pack["formatVersion"].as_u64() != Some(MMC_PACK_FORMAT_VERSION) {
+        return Err(invalid("formatVersion"));
+    }
+    let components = pack["components"]
+        .as_array()
+        .ok_or_else(|| invalid("components"))?;
+    let mut uids = Vec::new();
+    for component in components {
+        let uid = component["uid"]
+            .as_str()
+            .filter(|uid| !uid.is_empty())
+            .ok_or_else(|| invalid("uid"))?;
+        if uids.contains(&uid) {
+            return Err(invalid(uid));
+        }
+        uids.push(uid);
+    }
+
+    let lwjgl = if uids.contains(&"org.lwjgl3") {
+        "org.lwjgl3"
+    } else {
+        "org.lwjgl"
+    };
+    let mut required = vec![
+        lwjgl,
+        "net.minecraft",
+        "net.fabricmc.intermediary",
+        loader_type.get_maven_uid(),
+    ];
+    if include_flap {
+        required.push("net.ornithemc.flap");
+    }
+    match required.into_iter().find(|uid| !uids.contains(uid)) {
+        Some(missing) => Err(InstallerError::from(t!(
+            "mmc.error.missing_component",
+            uid = missing
+        ))),
+        None => Ok(()),
+    }
+}
+
 async fn transform_intermediary_patch(
     version: &MinecraftVersion,
     intermediary_version: &str,
@@ -334,12 +713,16 @@ async fn get_mmc_launch_json(
     generation: &Option<u32>,
     lwjgl_version: &String,
     ornithe_launch_json: &Value,
-) -> Result<String, InstallerError> {
+) -> Result<(String, u32), InstallerError> {
     let client_name = format!("com.mojang:minecraft:{}:client", version.id);
     let (_, vanilla_launch_json) = manifest::fetch_launch_json(version, generation).await?;
     let vanilla_json = serde_json::from_str::<Value>(&vanilla_launch_json)?;
 
     let client = vanilla_json["downloads"]["client"].as_object().unwrap();
+    // Versions from before the javaVersion field was introduced all run on Java 8
+    let required_java = vanilla_json["javaVersion"]["majorVersion"]
+        .as_u64()
+        .unwrap_or(8) as u32;
 
     let main_jar = json!({
         "downloads": {
@@ -421,5 +804,242 @@ async fn get_mmc_launch_json(
             .insert("+jvmArgs".to_owned(), json!(jvm_arguments));
     }
 
-    Ok(serde_json::to_string_pretty(&json)?)
+    Ok((serde_json::to_string_pretty(&json)?, required_java))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::actions::Writer;
+
+    fn minecraft_version() -> MinecraftVersion {
+        serde_json::from_value(json!({
+            "id": "1.8.9",
+            "type": "release",
+            "url": "https://example.com/1.8.9.json",
+            "releaseTime": "2015-12-03T09:24:39+00:00",
+            "details": "https://example.com/1.8.9-details.json"
+        }))
+        .unwrap()
+    }
+
+    fn loader_version() -> LoaderVersion {
+        serde_json::from_value(json!({
+            "version": "0.16.14",
+            "stable": true,
+            "maven": "net.fabricmc:fabric-loader:0.16.14",
+            "separator": ".",
+            "build": 14
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pack_json_substitutes_versions() {
+        let pack = transform_pack_json(
+            &minecraft_version(),
+            &LoaderType::Fabric,
+            &loader_version(),
+            "2.9.4",
+            "1.8.9",
+        )
+        .await
+        .unwrap();
+        assert!(!pack.contains("${"));
+
+        let pack = serde_json::from_str::<Value>(&pack).unwrap();
+        let components = pack["components"].as_array().unwrap();
+        assert_eq!(components[0]["uid"], "org.lwjgl");
+        assert_eq!(components[0]["cachedName"], "LWJGL 2");
+        assert_eq!(components[0]["version"], "2.9.4");
+        assert_eq!(components[1]["version"], "1.8.9");
+        assert_eq!(components[2]["cachedVersion"], "1.8.9");
+        assert_eq!(components[3]["cachedName"], "Fabric Loader");
+        assert_eq!(components[3]["uid"], LoaderType::Fabric.get_maven_uid());
+        assert_eq!(components[3]["version"], "0.16.14");
+    }
+
+    #[tokio::test]
+    async fn pack_json_uses_lwjgl3_uid() {
+        let pack = transform_pack_json(
+            &minecraft_version(),
+            &LoaderType::Quilt,
+            &loader_version(),
+            "3.3.3",
+            "1.8.9",
+        )
+        .await
+        .unwrap();
+        let pack = serde_json::from_str::<Value>(&pack).unwrap();
+        assert_eq!(pack["components"][0]["uid"], "org.lwjgl3");
+        assert_eq!(
+            pack["components"][1]["cachedRequires"][0]["uid"],
+            "org.lwjgl3"
+        );
+    }
+
+    #[tokio::test]
+    async fn intermediary_patch_is_valid_json() {
+        let patch = transform_intermediary_patch(
+            &minecraft_version(),
+            "1.8.9",
+            "net.ornithemc:calamus-intermediary-gen2",
+        )
+        .await
+        .unwrap();
+
+        let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+        files
+            .write_file("patches/net.fabricmc.intermediary.json", patch.as_bytes())
+            .unwrap();
+        let patch =
+            serde_json::from_slice::<Value>(&files["patches/net.fabricmc.intermediary.json"])
+                .unwrap();
+        assert_eq!(
+            patch["libraries"][0]["name"],
+            "net.ornithemc:calamus-intermediary-gen2:1.8.9"
+        );
+        assert_eq!(patch["requires"][0]["equals"], "1.8.9");
+        assert_eq!(patch["version"], "1.8.9");
+    }
+
+    #[test]
+    fn flap_component_is_added() {
+        let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut components = Vec::new();
+        add_flap_component(&mut files, &mut components, "0.2.0").unwrap();
+
+        let patch =
+            serde_json::from_slice::<Value>(&files["patches/net.ornithemc.flap.json"]).unwrap();
+        assert_eq!(patch["uid"], "net.ornithemc.flap");
+        assert_eq!(patch["+agents"][0]["name"], "net.ornithemc:flap:0.2.0");
+        assert_eq!(patch["+agents"][0]["url"], maven::MAVEN_URL);
+
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0]["uid"], "net.ornithemc.flap");
+        assert_eq!(components[0]["cachedVersion"], "0.2.0");
+    }
+
+    #[tokio::test]
+    async fn pack_json_has_all_components() {
+        let pack = transform_pack_json(
+            &minecraft_version(),
+            &LoaderType::Fabric,
+            &loader_version(),
+            "2.9.4",
+            "1.8.9",
+        )
+        .await
+        .unwrap();
+        let mut pack = serde_json::from_str::<Value>(&pack).unwrap();
+        validate_pack_json(&pack, &LoaderType::Fabric, false).unwrap();
+        assert!(validate_pack_json(&pack, &LoaderType::Fabric, true).is_err());
+
+        let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+        add_flap_component(
+            &mut files,
+            pack["components"].as_array_mut().unwrap(),
+            "0.2.0",
+        )
+        .unwrap();
+        validate_pack_json(&pack, &LoaderType::Fabric, true).unwrap();
+
+        let uids = pack["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|component| component["uid"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            uids,
+            [
+                "org.lwjgl",
+                "net.minecraft",
+                "net.fabricmc.intermediary",
+                LoaderType::Fabric.get_maven_uid(),
+                "net.ornithemc.flap"
+            ]
+        );
+    }
+
+    #[test]
+    fn pack_json_without_loader_is_rejected() {
+        let pack = json!({
+            "formatVersion": 1,
+            "components": [
+                {"uid": "org.lwjgl"},
+                {"uid": "net.minecraft"},
+                {"uid": "net.fabricmc.intermediary"}
+            ]
+        });
+        assert!(validate_pack_json(&pack, &LoaderType::Quilt, false).is_err());
+
+        let mut pack = pack;
+        pack["components"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({"uid": LoaderType::Quilt.get_maven_uid()}));
+        validate_pack_json(&pack, &LoaderType::Quilt, false).unwrap();
+
+        pack["formatVersion"] = json!(2);
+        assert!(validate_pack_json(&pack, &LoaderType::Quilt, false).is_err());
+    }
+
+    #[test]
+    fn window_settings_only_written_when_set() {
+        assert_eq!(WindowSettings::default().instance_config(), "");
+        let window = WindowSettings {
+            width: Some(1280),
+            height: None,
+            fullscreen: true,
+        };
+        assert_eq!(
+            window.instance_config(),
+            "\nOverrideWindow=true\nMinecraftWinWidth=1280\nLaunchMaximized=true"
+        );
+    }
+
+    #[test]
+    fn java_note_only_for_java_8() {
+        assert_eq!(java_note(21), "");
+        assert!(java_note(8).starts_with("\nnotes=\""));
+    }
+
+    #[test]
+    fn identical_patches_are_shared() {
+        let instance = |name: &str, minecraft: &[u8]| Instance {
+            profile_name: name.to_owned(),
+            generation: 2,
+            files: HashMap::from([
+                ("mmc-pack.json".to_owned(), b"{}".to_vec()),
+                ("patches/org.ow2.asm.json".to_owned(), b"asm".to_vec()),
+                ("patches/net.minecraft.json".to_owned(), minecraft.to_vec()),
+            ]),
+        };
+        let files = share_patches(&[instance("a", b"1.8.9"), instance("b", b"1.12.2")]).unwrap();
+
+        let components = files
+            .keys()
+            .filter(|path| path.starts_with("components/"))
+            .collect::<Vec<_>>();
+        assert_eq!(components.len(), 3);
+        assert!(files.contains_key("a/mmc-pack.json"));
+        assert!(!files.contains_key("a/patches/org.ow2.asm.json"));
+
+        let references = |name: &str| {
+            serde_json::from_slice::<Value>(&files[&format!("{name}/components.json")]).unwrap()
+        };
+        let (a, b) = (references("a"), references("b"));
+        assert_eq!(a["patches/org.ow2.asm.json"], b["patches/org.ow2.asm.json"]);
+        assert_ne!(
+            a["patches/net.minecraft.json"],
+            b["patches/net.minecraft.json"]
+        );
+        assert_eq!(
+            files[a["patches/net.minecraft.json"].as_str().unwrap()],
+            b"1.8.9"
+        );
+    }
 }
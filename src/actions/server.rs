@@ -1,5 +1,5 @@
 use std::{
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
     io::{Cursor, Read, Write},
     path::{Path, PathBuf},
     process::{Command, Stdio},
@@ -7,7 +7,11 @@ use std::{
     time::Duration,
 };
 
-use serde_json::json;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
 use tokio::sync::mpsc::UnboundedSender;
 use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
 
@@ -165,11 +169,25 @@ async fn install_path(
     let libraries = launch_json["libraries"]
         .as_array()
         .ok_or(InstallerError::from(t!("server.error.no_libraries")))?;
+    let (libraries, conflicts) = dedupe_libraries(libraries);
+    for (kept, dropped) in conflicts {
+        let _ = sender.send((
+            0.2,
+            t!(
+                "server.info.library_conflict",
+                kept = kept,
+                dropped = dropped
+            )
+            .into(),
+        ));
+    }
 
     #[cfg(not(target_arch = "wasm32"))]
     let mut library_files = tokio::task::JoinSet::new();
     #[cfg(not(target_arch = "wasm32"))]
-    let mut lib_count = libraries.len();
+    let lib_count = libraries.len() + usize::from(include_flap);
+    #[cfg(not(target_arch = "wasm32"))]
+    let sequential = SEQUENTIAL.load(Ordering::Relaxed);
     #[cfg(target_arch = "wasm32")]
     let mut w = zip::ZipWriter::new(Cursor::new(Vec::new()));
     #[cfg(target_arch = "wasm32")]
@@ -178,6 +196,13 @@ async fn install_path(
     let library_dir = location.join("libraries");
 
     let mut downloaded_library_files = Vec::new();
+    // Downloads take up most of the time, so the bar advances per downloaded file
+    let mut progress = super::ProgressTracker::new(
+        &sender,
+        0.2,
+        0.95,
+        libraries.len() + usize::from(include_flap) + usize::from(install_server),
+    );
     for library in libraries {
         let name = library["name"]
             .as_str()
@@ -188,6 +213,10 @@ async fn install_path(
             .ok_or(InstallerError::from(t!("server.error.no_library_url")))?
             .to_owned();
 
+        let sha1 = library["sha1"].as_str().map(|s| s.to_owned());
+        #[cfg(not(target_arch = "wasm32"))]
+        let size = library["size"].as_u64();
+
         if name.matches("net\\.fabricmc:fabric-loader:.*").count() > 0 {
             fabric_loader_artifact = Some(name.clone());
         }
@@ -195,35 +224,26 @@ async fn install_path(
         #[cfg(not(target_arch = "wasm32"))]
         {
             let dir = library_dir.clone();
-            let fut = async move { download_library(&dir, name, url).await };
+            let fut = async move { download_library(&dir, name, url, sha1, size).await };
             library_files.spawn(fut);
+            if sequential && let Some(done) = library_files.join_next().await {
+                record_library_download(
+                    done,
+                    &mut downloaded_library_files,
+                    &mut progress,
+                    lib_count,
+                )?;
+            }
         }
         #[cfg(target_arch = "wasm32")]
         {
-            let parts = name.splitn(3, ":").collect::<Vec<&str>>();
-            let group = parts.get(0).unwrap().replace(".", "/");
-            let name = parts.get(1).unwrap();
-            let version = parts.get(2).unwrap();
-            let dir = format!(
-                "{}/libraries/{}/{}/{}/",
-                location.display(),
-                group,
-                name,
-                version
-            );
-            let path = format!(
-                "{}/libraries/{}/{}/{}/{}-{}.jar",
-                location.display(),
-                group,
-                name,
-                version,
-                name,
-                version
-            );
+            let artifact_path = split_artifact(&name)?;
+            let path = format!("{}/libraries/{}", location.display(), artifact_path);
+            let dir = format!("{}/", path.rsplit_once('/').unwrap_or_default().0);
             let bytes = crate::net::get_bytes_client(
                 &crate::net::UNCONFIGURED_CLIENT,
                 format!(
-                    "{}/{}/{}/{}/{}-{}.jar",
+                    "{}/{}",
                     url.replace(
                         // libraries.minecraft.net does not send CORS headers.
                         // This more or less abuses LF's maven as a proxy to central which *hopefully* contains all relevant artifacts.
@@ -231,27 +251,23 @@ async fn install_path(
                         "https://libraries.minecraft.net",
                         "https://repo.legacyfabric.net/central"
                     ),
-                    group,
-                    name,
-                    version,
-                    name,
-                    version
+                    artifact_path
                 )
                 .replace("//", "/"),
             )
             .await?;
+            if let Some(sha1) = sha1 {
+                crate::net::verify_sha1(&name, &sha1, &crate::net::sha1(&bytes))?;
+            }
             writer.create_dir(&dir)?;
             writer.write_file(&path, &bytes)?;
             downloaded_library_files.push(Path::new(&path).to_path_buf());
-            let _ = sender.send((
-                (downloaded_library_files.len() as f32 / libraries.len() as f32) / 2.0 + 0.2,
-                String::new(),
-            ));
+            progress.advance(String::new());
         }
     }
 
     let flap_version = if include_flap {
-        Some(maven::get_latest_version("flap").await?)
+        Some(maven::get_flap_version().await?)
     } else {
         None
     };
@@ -265,7 +281,7 @@ async fn install_path(
     };
     #[cfg(target_arch = "wasm32")]
     if include_flap {
-        let bytes = maven::get_latest_release_file("flap").await?;
+        let bytes = maven::get_flap_file().await?;
         writer.create_dir(&format!(
             "{}/libraries/net/ornithemc/flap",
             location.display()
@@ -279,82 +295,76 @@ async fn install_path(
             &bytes,
         )?;
         downloaded_library_files.push(flap_path.as_ref().unwrap().clone());
+        progress.advance(String::new());
     }
     #[cfg(not(target_arch = "wasm32"))]
     {
         if include_flap {
             let out_path = flap_path.as_ref().unwrap().clone();
             let fut = async move {
-                maven::download_latest_release("flap", &out_path).await?;
+                maven::download_flap(&out_path).await?;
                 Ok(out_path)
             };
             library_files.spawn(fut);
-            lib_count += 1;
         }
 
         while let Some(done) = library_files.join_next().await {
-            match done {
-                Ok(res) => match res {
-                    Ok(file) => {
-                        let name = file
-                            .file_name()
-                            .map(|o| o.to_string_lossy().to_string())
-                            .unwrap_or("??.jar".to_string());
-                        downloaded_library_files.push(file);
-                        let num = downloaded_library_files.len();
-                        let _ = sender.send((
-                            (num as f32 / lib_count as f32) / 2.0 + 0.2,
-                            t!(
-                                "server.info.downloaded_library",
-                                name = name,
-                                num = num,
-                                lib_count = lib_count
-                            )
-                            .into(),
-                        ));
-                    }
-                    Err(e) => {
-                        return Err(InstallerError::from(t!(
-                            "server.error.library_failed",
-                            error = e.0
-                        )));
-                    }
-                },
-                Err(e) => {
-                    return Err(InstallerError::from(t!(
-                        "server.error.libraries_failed",
-                        error = e.to_string()
-                    )));
-                }
-            }
+            record_library_download(
+                done,
+                &mut downloaded_library_files,
+                &mut progress,
+                lib_count,
+            )?;
         }
     }
 
-    let _ = sender.send((
-        0.8,
-        t!(
-            "server.info.downloaded_libraries",
-            lib_count = downloaded_library_files.len()
-        )
-        .into(),
+    progress.message(t!(
+        "server.info.downloaded_libraries",
+        lib_count = crate::format::format_count(downloaded_library_files.len() as u64)
     ));
 
     if cfg!(not(target_arch = "wasm32"))
         && let Some(loader) = fabric_loader_artifact
     {
-        let lib = location.join("libraries").join(split_artifact(&loader));
+        let lib = location.join("libraries").join(split_artifact(&loader)?);
         launch_main_class = read_jar_manifest_attribute(&lib, "Main-Class")?;
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(class) = MAIN_CLASS_OVERRIDE.get() {
+        // Asked for explicitly, so this must not fail under --strict like a warning would
+        let _ = sender.send((
+            0.95,
+            t!(
+                "server.info.main_class_override",
+                class = class,
+                default = launch_main_class
+            )
+            .into(),
+        ));
+        launch_main_class = class.clone();
+    }
 
     #[cfg(not(target_arch = "wasm32"))]
     if !location.exists() {
         std::fs::create_dir_all(&location)?;
     }
 
+    let calamus_gen = match generation {
+        Some(g) => *g,
+        None => {
+            crate::net::meta::fetch_intermediary_generations()
+                .await?
+                .stable
+        }
+    };
+
     create_launch_jar(
         version,
         &location,
         loader_type,
+        loader_version,
+        intermediary,
+        calamus_gen,
         main_class,
         &launch_main_class,
         &downloaded_library_files,
@@ -366,30 +376,32 @@ async fn install_path(
     .await?;
 
     if install_server {
-        let _ = sender.send((0.9, t!("server.info.downloading_server_jar").into()));
+        progress.message(t!("server.info.downloading_server_jar"));
         let url = version
             .get_jar_download_url(&crate::net::GameSide::Server)
             .await?;
         #[cfg(target_arch = "wasm32")]
         {
             let bytes =
-                crate::net::get_bytes_client(&crate::net::UNCONFIGURED_CLIENT, url.url).await?;
+                crate::net::get_bytes_client(&crate::net::UNCONFIGURED_CLIENT, &url.url).await?;
+            crate::net::verify_sha1("server.jar", &url.sha1, &crate::net::sha1(&bytes))?;
             writer.write_file("server.jar", &bytes)?;
         }
         #[cfg(not(target_arch = "wasm32"))]
-        crate::net::download_file(&url.url, &location.join("server.jar")).await?;
+        {
+            let sha1 = crate::net::download_file(
+                &url.url,
+                &location.join("server.jar"),
+                Some(url.size as u64),
+            )
+            .await?;
+            crate::net::verify_sha1("server.jar", &url.sha1, &sha1)?;
+        }
+        progress.advance(String::new());
     }
 
     #[cfg(target_arch = "wasm32")]
     {
-        let calamus_gen = match generation {
-            Some(g) => g,
-            None => {
-                &crate::net::meta::fetch_intermediary_generations()
-                    .await?
-                    .stable
-            }
-        };
         let name = format!(
             "ornithe-gen{calamus_gen}-{}-{}-server.zip",
             loader_type.get_name(),
@@ -402,10 +414,84 @@ async fn install_path(
     Ok(())
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+static LAUNCHER_JAR_OVERRIDE: std::sync::OnceLock<Vec<u8>> = std::sync::OnceLock::new();
+
+/// For testing launcher changes without rebuilding. Like the embedded jar it needs a
+/// manifest naming a Main-Class.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_launcher_jar(path: &Path) -> Result<(), InstallerError> {
+    let bytes = std::fs::read(path)?;
+    let invalid = || {
+        InstallerError::from(t!(
+            "server.error.invalid_launcher_jar",
+            file = path.display()
+        ))
+    };
+    let mut jar = ZipArchive::new(Cursor::new(&bytes)).map_err(|_| invalid())?;
+    let manifest =
+        std::io::read_to_string(jar.by_name("META-INF/MANIFEST.MF").map_err(|_| invalid())?)?;
+    if !manifest
+        .lines()
+        .any(|line| line.starts_with("Main-Class: "))
+    {
+        return Err(invalid());
+    }
+    let _ = LAUNCHER_JAR_OVERRIDE.set(bytes);
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static SEQUENTIAL: AtomicBool = AtomicBool::new(false);
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_sequential(sequential: bool) {
+    SEQUENTIAL.store(sequential, Ordering::Relaxed);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static MAIN_CLASS_OVERRIDE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// Unsupported, and only checked to look like a java class name.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_main_class(class: &str) -> Result<(), InstallerError> {
+    if !is_class_name(class) {
+        return Err(InstallerError::from(t!(
+            "server.error.invalid_main_class",
+            class = class
+        )));
+    }
+    let _ = MAIN_CLASS_OVERRIDE.set(class.to_owned());
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_class_name(name: &str) -> bool {
+    let is_identifier = |part: &str| {
+        let mut chars = part.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    };
+    name.split('.').all(is_identifier)
+}
+
+fn launcher_jar() -> &'static [u8] {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(jar) = LAUNCHER_JAR_OVERRIDE.get() {
+        return jar;
+    }
+    SERVER_LAUNCHER_JAR
+}
+
 async fn create_launch_jar(
     version: &MinecraftVersion,
     install_location: &PathBuf,
     loader_type: &LoaderType,
+    loader_version: &LoaderVersion,
+    intermediary: &IntermediaryVersion,
+    calamus_gen: u32,
     main_class: &str,
     launch_main_class: &str,
     library_files: &Vec<PathBuf>,
@@ -427,7 +513,7 @@ async fn create_launch_jar(
     let mut buf = Cursor::new(Vec::new());
     #[cfg(target_arch = "wasm32")]
     let mut zip = ZipWriter::new(&mut buf);
-    let mut launch_jar = ZipArchive::new(Cursor::new(SERVER_LAUNCHER_JAR))?;
+    let mut launch_jar = ZipArchive::new(Cursor::new(launcher_jar()))?;
     let mut manifest = Vec::new();
     for i in 0..launch_jar.len() {
         let f = launch_jar.by_index_raw(i)?;
@@ -451,7 +537,7 @@ async fn create_launch_jar(
         )?;
     }
 
-    let mut class_path = String::from("Class-Path: ");
+    let mut class_path = String::new();
     for library in library_files {
         let relative = library.strip_prefix(install_location)?.to_str();
         if let Some(p) = relative {
@@ -459,22 +545,32 @@ async fn create_launch_jar(
         }
     }
 
+    // Without flap the server launcher is not used, but the arguments are still
+    // recorded so that `server run` can apply the recommended jvm arguments itself.
+    let mut ornithe_args = json!({
+        "main_class": launch_main_class,
+        "jvm_args": jvm_args
+    });
     if let Some(flap_path) = flap_jar_path
         && let Some(path) = flap_path.strip_prefix(install_location)?.to_str()
     {
-        zip.start_file("ornithe-args.json", SimpleFileOptions::default())?;
-        zip.write_all(&serde_json::to_vec(&json!({
-            "flap_jar": path.replace("\\", "/"),
-            "main_class": launch_main_class,
-            "jvm_args": jvm_args
-        }))?)?;
-    }
-
-    writeln!(manifest, "{}\r", wrap_manifest_line(class_path.trim_end()))?;
-    writeln!(
-        manifest,
-        "{}\r",
-        wrap_manifest_line(&format!("Minecraft-Version: {}\r", version.id))
+        ornithe_args["flap_jar"] = json!(path.replace("\\", "/"));
+    }
+    zip.start_file("ornithe-args.json", SimpleFileOptions::default())?;
+    zip.write_all(&super::to_json(&ornithe_args)?)?;
+
+    let calamus_gen = calamus_gen.to_string();
+    write_manifest_attributes(
+        &mut manifest,
+        &[
+            ("Class-Path", class_path.trim_end()),
+            ("Minecraft-Version", &version.id),
+            // Provenance of the installation, for tools inspecting the jar and reinstall checks
+            ("Ornithe-Loader", loader_type.get_name()),
+            ("Ornithe-Loader-Version", &loader_version.version),
+            ("Ornithe-Intermediary", &intermediary.maven),
+            ("Ornithe-Calamus-Generation", &calamus_gen),
+        ],
     )?;
     zip.start_file("META-INF/MANIFEST.MF", SimpleFileOptions::default())?;
     zip.write_all(&manifest)?;
@@ -500,6 +596,20 @@ async fn create_launch_jar(
     Ok(())
 }
 
+fn write_manifest_attributes(
+    manifest: &mut Vec<u8>,
+    attributes: &[(&str, &str)],
+) -> Result<(), InstallerError> {
+    for (attribute, value) in attributes {
+        writeln!(
+            manifest,
+            "{}\r",
+            wrap_manifest_line(&format!("{attribute}: {value}"))
+        )?;
+    }
+    Ok(())
+}
+
 fn wrap_manifest_line(line: &str) -> String {
     let mut res = String::new();
     let mut count = 0;
@@ -543,27 +653,270 @@ fn read_jar_manifest_attribute(
     )))
 }
 
+#[derive(Deserialize)]
+struct OrnitheArgs {
+    flap_jar: Option<String>,
+    #[serde(default)]
+    jvm_args: Vec<String>,
+}
+
+fn read_ornithe_args(jar_file: &PathBuf) -> Option<OrnitheArgs> {
+    let file = std::fs::File::open(jar_file).ok()?;
+    let mut zip = ZipArchive::new(file).ok()?;
+    let args = zip.by_name("ornithe-args.json").ok()?;
+    serde_json::from_reader(args).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn library_downloads(
+    launch_json: &Value,
+) -> Result<Vec<(String, String, Option<u64>)>, InstallerError> {
+    let libraries = launch_json["libraries"]
+        .as_array()
+        .ok_or(InstallerError::from(t!("server.error.no_libraries")))?;
+    dedupe_libraries(libraries)
+        .0
+        .into_iter()
+        .map(|library| {
+            let name = library["name"]
+                .as_str()
+                .ok_or(InstallerError::from(t!("server.error.no_library_name")))?;
+            let url = library["url"]
+                .as_str()
+                .ok_or(InstallerError::from(t!("server.error.no_library_url")))?;
+            Ok((
+                name.to_owned(),
+                url.to_owned() + &split_artifact(name)?,
+                library["size"].as_u64(),
+            ))
+        })
+        .collect()
+}
+
+/// Keeps the highest version of each `group:artifact[:classifier]`, so the classpath
+/// never holds two versions of a library.
+fn dedupe_libraries(libraries: &[Value]) -> (Vec<&Value>, Vec<(String, String)>) {
+    let mut kept: Vec<&Value> = Vec::with_capacity(libraries.len());
+    let mut conflicts = Vec::new();
+    for library in libraries {
+        let Some(name) = library["name"].as_str() else {
+            // Reported as a missing name when downloading
+            kept.push(library);
+            continue;
+        };
+        let (key, version) = library_key(name);
+        let existing = kept.iter_mut().find(|other| {
+            other["name"]
+                .as_str()
+                .is_some_and(|other| library_key(other).0 == key)
+        });
+        match existing {
+            None => kept.push(library),
+            Some(existing) => {
+                let existing_name = existing["name"].as_str().unwrap_or_default().to_owned();
+                if existing_name == name {
+                    continue;
+                }
+                if compare_versions(version, library_key(&existing_name).1).is_gt() {
+                    *existing = library;
+                    conflicts.push((name.to_owned(), existing_name));
+                } else {
+                    conflicts.push((existing_name, name.to_owned()));
+                }
+            }
+        }
+    }
+    (kept, conflicts)
+}
+
+fn library_key(name: &str) -> (String, &str) {
+    let parts = name.split(':').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [group, artifact, version, classifier @ ..] => {
+            let mut key = format!("{group}:{artifact}");
+            for classifier in classifier {
+                key += ":";
+                key += classifier;
+            }
+            (key, version)
+        }
+        _ => (name.to_owned(), ""),
+    }
+}
+
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let mut a_parts = a.split(['.', '-', '+']);
+    let mut b_parts = b.split(['.', '-', '+']);
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn record_library_download(
+    done: Result<Result<PathBuf, InstallerError>, tokio::task::JoinError>,
+    downloaded_library_files: &mut Vec<PathBuf>,
+    progress: &mut super::ProgressTracker,
+    lib_count: usize,
+) -> Result<(), InstallerError> {
+    match done {
+        Ok(Ok(file)) => {
+            let name = file
+                .file_name()
+                .map(|o| o.to_string_lossy().to_string())
+                .unwrap_or("??.jar".to_string());
+            downloaded_library_files.push(file);
+            let num = downloaded_library_files.len();
+            progress.advance(t!(
+                "server.info.downloaded_library",
+                name = name,
+                num = crate::format::format_count(num as u64),
+                lib_count = crate::format::format_count(lib_count as u64)
+            ));
+            Ok(())
+        }
+        Ok(Err(e)) => Err(InstallerError::from(t!(
+            "server.error.library_failed",
+            error = e.0
+        ))),
+        Err(e) => Err(InstallerError::from(t!(
+            "server.error.libraries_failed",
+            error = e.to_string()
+        ))),
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 async fn download_library(
     libraries_dir: &Path,
     name: String,
     url: String,
+    sha1: Option<String>,
+    size: Option<u64>,
 ) -> Result<PathBuf, InstallerError> {
-    let split_artifact = split_artifact(&name);
-    let file = libraries_dir.join(&split_artifact);
+    let split_artifact = split_artifact(&name)?;
+    let file = super::long_path(libraries_dir).join(&split_artifact);
     let raw_url = url.to_owned() + &split_artifact;
-    crate::net::download_file(&raw_url, &file).await?;
+    let actual = crate::net::download_file(&raw_url, &file, size).await?;
+    if let Some(expected) = sha1 {
+        crate::net::verify_sha1(&name, &expected, &actual)?;
+    }
 
     Ok(file)
 }
 
-fn split_artifact(artifact: &str) -> String {
-    let parts = artifact.splitn(3, ":").collect::<Vec<&str>>();
-    let group = parts.first().unwrap().replace(".", "/");
-    let name = parts.get(1).unwrap();
-    let version = parts.get(2).unwrap();
+fn split_artifact(artifact: &str) -> Result<String, InstallerError> {
+    let invalid = || InstallerError::from(t!("server.error.invalid_library_name", name = artifact));
+    let parts = artifact.split(':').collect::<Vec<&str>>();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(invalid());
+    }
+    let (group, name, version, classifier) = match parts[..] {
+        [group, name, version] => (group, name, version, None),
+        [group, name, version, classifier] => (group, name, version, Some(classifier)),
+        _ => return Err(invalid()),
+    };
+
+    let file = match classifier {
+        Some(classifier) => format!("{name}-{version}-{classifier}.jar"),
+        None => format!("{name}-{version}.jar"),
+    };
+    Ok(format!(
+        "{}/{name}/{version}/{file}",
+        group.replace('.', "/")
+    ))
+}
+
+/// Extracting the zip in the server directory restores the layout the launch jar expects.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pack_libraries(
+    sender: &UnboundedSender<(f32, String)>,
+    location: &Path,
+) -> Result<PathBuf, InstallerError> {
+    let archive = location.join("libraries.zip");
+    let mut zip = ZipWriter::new(std::fs::File::create(&archive)?);
+    let options = SimpleFileOptions::default();
+    let mut pending = vec![location.join("libraries")];
+    while let Some(dir) = pending.pop() {
+        let mut entries = std::fs::read_dir(&dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        for path in entries {
+            let name = path
+                .strip_prefix(location)
+                .unwrap_or(&path)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if path.is_dir() {
+                zip.add_directory(name, options)?;
+                pending.push(path);
+            } else {
+                zip.start_file(name, options)?;
+                std::io::copy(&mut std::fs::File::open(&path)?, &mut zip)?;
+            }
+        }
+    }
+    zip.finish()?;
+    let _ = sender.send((
+        1.0,
+        t!("server.info.packed_libraries", file = archive.display()).into(),
+    ));
+    Ok(archive)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn accept_eula(location: &Path) -> Result<(), InstallerError> {
+    std::fs::create_dir_all(location)?;
+    std::fs::write(
+        location.join("eula.txt"),
+        super::text_file(
+            "# Accepted through the Ornithe installer, see https://aka.ms/MinecraftEULA\neula=true\n",
+        ),
+    )?;
+    Ok(())
+}
+
+fn launch_jar_path(location: &Path, loader_type: &LoaderType) -> PathBuf {
+    location.join(loader_type.get_name().to_owned() + "-server-launch.jar")
+}
+
+pub fn installed_version(location: &Path, loader_type: &LoaderType) -> Option<String> {
+    installed_attribute(location, loader_type, "Minecraft-Version")
+}
+
+fn installed_attribute(
+    location: &Path,
+    loader_type: &LoaderType,
+    attribute: &str,
+) -> Option<String> {
+    let launch_jar = launch_jar_path(location, loader_type);
+    if !launch_jar.exists() {
+        return None;
+    }
+    read_jar_manifest_attribute(&launch_jar, attribute).ok()
+}
 
-    group + "/" + name + "/" + version + "/" + name + "-" + version + ".jar"
+fn server_args(post_args: Vec<String>, gui: bool) -> Vec<String> {
+    let mut args = Vec::with_capacity(post_args.len() + 1);
+    if !gui {
+        args.push("nogui".to_owned());
+    }
+    args.extend(post_args);
+    args
 }
 
 pub async fn install_and_run<I, S>(
@@ -577,26 +930,49 @@ pub async fn install_and_run<I, S>(
     include_flap: bool,
     java: Option<&PathBuf>,
     args: Option<I>,
+    post_args: Vec<String>,
+    gui: bool,
 ) -> Result<bool, InstallerError>
 where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    let launch_jar = location.join(loader_type.get_name().to_owned() + "-server-launch.jar");
-    let mut needs_install = false;
+    let launch_jar = launch_jar_path(&location, &loader_type);
     let _ = sender.send((
         0.0,
         "Checking for present server installation...".to_string(),
     ));
-    if !launch_jar.exists() {
-        needs_install = true;
+    let installed = installed_version(&location, &loader_type);
+    if let Some(installed) = &installed
+        && *installed != version.id
+    {
+        let _ = sender.send((
+            0.0,
+            t!(
+                "server.info.replacing_version",
+                installed = installed,
+                version = version.id
+            )
+            .into(),
+        ));
     }
-
-    if !needs_install {
-        needs_install = read_jar_manifest_attribute(&launch_jar, "Minecraft-Version")
-            .map(|v| v.trim_ascii() != version.id)
-            .unwrap_or(true);
+    let calamus_gen = match generation {
+        Some(g) => g,
+        None => {
+            crate::net::meta::fetch_intermediary_generations()
+                .await?
+                .stable
+        }
+    };
+    // Launch jars written before these were recorded are reinstalled as well
+    let same_loader = installed_attribute(&location, &loader_type, "Ornithe-Loader-Version")
+        .is_some_and(|installed| installed == loader_version.version)
+        && installed_attribute(&location, &loader_type, "Ornithe-Calamus-Generation")
+            .is_some_and(|installed| installed == calamus_gen.to_string());
+    if installed.as_deref() == Some(version.id.as_str()) && !same_loader {
+        let _ = sender.send((0.0, t!("server.info.replacing_loader").into()));
     }
+    let needs_install = installed.as_deref() != Some(version.id.as_str()) || !same_loader;
 
     if needs_install {
         install_path(
@@ -628,10 +1004,51 @@ where
         .stdout(Stdio::inherit())
         .stdin(Stdio::inherit())
         .stderr(Stdio::inherit());
-    if let Some(args) = args {
-        cmd.args(args);
+    let mut jvm_args = args
+        .map(|args| {
+            args.into_iter()
+                .map(|arg| arg.as_ref().to_os_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    match read_ornithe_args(&jar) {
+        // The server launcher applies the recommended arguments when relaunching with flap
+        Some(OrnitheArgs {
+            flap_jar: Some(_),
+            jvm_args: recommended,
+        }) if !recommended.is_empty() => {
+            let _ = sender.send((
+                0.95,
+                t!(
+                    "server.info.launcher_jvm_args",
+                    args = recommended.join(" ")
+                )
+                .into(),
+            ));
+        }
+        Some(OrnitheArgs {
+            flap_jar: None,
+            jvm_args: recommended,
+        }) => jvm_args.extend(recommended.into_iter().map(OsString::from)),
+        _ => {}
+    }
+    if !jvm_args.is_empty() {
+        let _ = sender.send((
+            0.95,
+            t!(
+                "server.info.jvm_args",
+                args = jvm_args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+            .into(),
+        ));
     }
-    cmd.arg("-jar").arg(jar).arg("nogui");
+
+    cmd.args(jvm_args);
+    cmd.arg("-jar").arg(jar).args(server_args(post_args, gui));
     let mut child = cmd.spawn()?;
     tokio::spawn(async move {
         thread::sleep(Duration::from_millis(100));
@@ -640,3 +1057,121 @@ where
 
     Ok(needs_install)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_args_follow_nogui() {
+        assert_eq!(server_args(Vec::new(), false), ["nogui"]);
+        assert_eq!(
+            server_args(vec!["--port".to_owned(), "25566".to_owned()], true),
+            ["--port", "25566"]
+        );
+    }
+
+    #[test]
+    fn manifest_attributes_stay_in_the_main_section() {
+        let class_path = "libraries/net/ornithemc/library.jar ".repeat(8);
+        let mut manifest = b"Manifest-Version: 1.0\r\n".to_vec();
+        write_manifest_attributes(
+            &mut manifest,
+            &[
+                ("Class-Path", class_path.trim_end()),
+                ("Minecraft-Version", "1.12.2"),
+                ("Ornithe-Calamus-Generation", "2"),
+            ],
+        )
+        .unwrap();
+        let manifest = String::from_utf8(manifest).unwrap();
+        // A blank line ends the main section, after which java expects a Name header
+        assert!(!manifest.contains("\r\n\r\n"), "{manifest:?}");
+        let lines = manifest.strip_suffix("\r\n").unwrap().split("\r\n");
+        for line in lines {
+            assert!(
+                !line.is_empty() && !line.contains('\r') && line.len() <= 72,
+                "{line:?}"
+            );
+        }
+        assert!(manifest.ends_with("\r\nOrnithe-Calamus-Generation: 2\r\n"));
+    }
+
+    #[test]
+    fn splits_artifacts() {
+        assert_eq!(
+            split_artifact("net.fabricmc:fabric-loader:0.16.14").unwrap(),
+            "net/fabricmc/fabric-loader/0.16.14/fabric-loader-0.16.14.jar"
+        );
+        assert_eq!(
+            split_artifact("org.lwjgl:lwjgl:3.3.3:natives-linux").unwrap(),
+            "org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-linux.jar"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_artifacts() {
+        for name in [
+            "",
+            "net.fabricmc",
+            "net.fabricmc:fabric-loader",
+            "a::1.0",
+            "a:b:1.0:",
+            "a:b:c:d:e",
+        ] {
+            let error = split_artifact(name).unwrap_err();
+            assert!(error.0.contains(name), "{name}: {}", error.0);
+        }
+    }
+
+    #[test]
+    fn checks_class_names() {
+        assert!(is_class_name(
+            "net.fabricmc.loader.launch.server.FabricServerLauncher"
+        ));
+        assert!(is_class_name("Wrapper$Main"));
+        for name in [
+            "",
+            "net..Main",
+            "net.fabricmc.",
+            "1net.Main",
+            "net/Main",
+            "net.Main ",
+        ] {
+            assert!(!is_class_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn keeps_highest_library_version() {
+        let libraries = vec![
+            json!({"name": "org.ow2.asm:asm:9.6", "url": "a"}),
+            json!({"name": "net.fabricmc:fabric-loader:0.16.14", "url": "b"}),
+            json!({"name": "org.ow2.asm:asm:9.10", "url": "c"}),
+            json!({"name": "org.ow2.asm:asm:9.2", "url": "d"}),
+            json!({"name": "net.fabricmc:fabric-loader:0.16.14", "url": "e"}),
+        ];
+        let (kept, conflicts) = dedupe_libraries(&libraries);
+        let names = kept
+            .iter()
+            .map(|library| library["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["org.ow2.asm:asm:9.10", "net.fabricmc:fabric-loader:0.16.14"]
+        );
+        assert_eq!(
+            conflicts,
+            [
+                (
+                    "org.ow2.asm:asm:9.10".to_owned(),
+                    "org.ow2.asm:asm:9.6".to_owned()
+                ),
+                (
+                    "org.ow2.asm:asm:9.10".to_owned(),
+                    "org.ow2.asm:asm:9.2".to_owned()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::errors::InstallerError;
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+pub fn cache_dir() -> Result<PathBuf, InstallerError> {
+    directories::ProjectDirs::from("net", "OrnitheMC", "ornithe-installer")
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .ok_or(InstallerError::from(t!("cache.error.no_cache_dir")))
+}
+
+fn metadata_file(url: &str) -> Result<PathBuf, InstallerError> {
+    Ok(cache_dir()?
+        .join("metadata")
+        .join(crate::net::sha1(url.as_bytes())))
+}
+
+pub fn store_metadata(url: &str, text: &str) {
+    let result = metadata_file(url).and_then(|file| {
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(std::fs::write(file, text)?)
+    });
+    if let Err(e) = result {
+        log::debug!("Failed to cache {url}: {}", e.0);
+    }
+}
+
+pub fn cached_metadata(url: &str) -> Option<String> {
+    std::fs::read_to_string(metadata_file(url).ok()?).ok()
+}
+
+pub fn has_metadata() -> bool {
+    cache_dir()
+        .and_then(|dir| Ok(std::fs::read_dir(dir.join("metadata"))?))
+        .is_ok_and(|mut entries| entries.next().is_some())
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+impl CacheStats {
+    fn add(&mut self, other: CacheStats) {
+        self.entries += other.entries;
+        self.bytes += other.bytes;
+    }
+}
+
+pub fn info() -> Result<Vec<(String, CacheStats)>, InstallerError> {
+    let dir = cache_dir()?;
+    let mut entries = Vec::new();
+    if !dir.exists() {
+        return Ok(entries);
+    }
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        entries.push((
+            entry.file_name().to_string_lossy().into_owned(),
+            stats(&entry.path())?,
+        ));
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
+pub fn clear() -> Result<CacheStats, InstallerError> {
+    let dir = cache_dir()?;
+    if !dir.exists() {
+        return Ok(CacheStats::default());
+    }
+    let freed = stats(&dir)?;
+    std::fs::remove_dir_all(&dir)?;
+    Ok(freed)
+}
+
+fn stats(path: &Path) -> Result<CacheStats, InstallerError> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(CacheStats {
+            entries: 1,
+            bytes: metadata.len(),
+        });
+    }
+    let mut total = CacheStats::default();
+    for entry in std::fs::read_dir(path)? {
+        total.add(stats(&entry?.path())?);
+    }
+    Ok(total)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", crate::format::format_count(bytes));
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{} {}", crate::format::format_decimal(size), UNITS[unit])
+}
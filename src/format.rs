@@ -0,0 +1,54 @@
+//! Number and date formatting following the active locale, for values shown to the user.
+//! The separators and date pattern come from the locale files like any other translation.
+
+use chrono::{DateTime, Utc};
+
+pub fn format_count(count: u64) -> String {
+    let separator = t!("format.thousands_separator");
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out += &separator;
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Exact halves round to the even digit, as Rust's own formatting does.
+pub fn format_decimal(value: f64) -> String {
+    format!("{value:.1}").replace('.', &t!("format.decimal_separator"))
+}
+
+pub fn format_date(time: &DateTime<Utc>) -> String {
+    time.format(&t!("format.date")).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_digits() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn rounds_halves_to_even() {
+        assert_eq!(format_decimal(2.25), "2.2");
+        assert_eq!(format_decimal(2.75), "2.8");
+        assert_eq!(format_decimal(1.04), "1.0");
+    }
+
+    #[test]
+    fn formats_dates() {
+        let time = DateTime::parse_from_rfc3339("2017-09-18T08:39:46+00:00")
+            .unwrap()
+            .to_utc();
+        assert_eq!(format_date(&time), "Sep 18, 2017");
+    }
+}
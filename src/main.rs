@@ -1,6 +1,9 @@
 #![allow(clippy::too_many_arguments)]
 mod actions;
+#[cfg(not(target_arch = "wasm32"))]
+mod cache;
 mod errors;
+mod format;
 mod net;
 mod ui;
 
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use reqwest::Url;
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -16,9 +17,13 @@ pub async fn fetch_versions(generation: &Option<u32>) -> Result<VersionManifest,
         None => LAUNCHER_META_URL.to_string(),
     };
     #[cfg(target_arch = "wasm32")]
-    return super::get_json_client::<VersionManifest>(&super::UNCONFIGURED_CLIENT, url).await;
+    let mut manifest =
+        super::get_json_client::<VersionManifest>(&super::UNCONFIGURED_CLIENT, &url).await?;
     #[cfg(not(target_arch = "wasm32"))]
-    return super::get_json::<VersionManifest>(url).await;
+    let mut manifest = super::get_json::<VersionManifest>(&url).await?;
+
+    manifest.resolve_urls(&url);
+    Ok(manifest)
 }
 
 pub async fn vanilla_profile_name(
@@ -38,20 +43,10 @@ pub async fn fetch_launch_json(
     generation: &Option<u32>,
 ) -> Result<(String, String), InstallerError> {
     #[cfg(target_arch = "wasm32")]
-    let res = super::get_text_client(&super::UNCONFIGURED_CLIENT, &version.url).await;
+    let mut json =
+        super::get_json_client::<Value>(&super::UNCONFIGURED_CLIENT, &version.url).await?;
     #[cfg(not(target_arch = "wasm32"))]
-    let res = super::get_text(&version.url).await;
-    let mut json = match res {
-        Ok(j) => match serde_json::from_str::<Value>(&j) {
-            Ok(v) => v,
-            Err(e) => {
-                return Err(InstallerError(format!("{}: {}", e, &j)));
-            }
-        },
-        Err(e) => {
-            return Err(e);
-        }
-    };
+    let mut json = super::get_json::<Value>(&version.url).await?;
 
     if let Some(val) = json.as_object_mut() {
         let version_id = vanilla_profile_name(&version.id, generation).await?;
@@ -64,6 +59,13 @@ pub async fn fetch_launch_json(
     )))
 }
 
+/// Versions that predate the `javaVersion` field need Java 8.
+#[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
+pub async fn required_java_version(version: &MinecraftVersion) -> Result<u32, InstallerError> {
+    let json = super::get_json::<Value>(&version.url).await?;
+    Ok(json["javaVersion"]["majorVersion"].as_u64().unwrap_or(8) as u32)
+}
+
 async fn fetch_version_details(
     version: &MinecraftVersion,
 ) -> Result<VersionDetails, InstallerError> {
@@ -81,6 +83,24 @@ pub struct VersionManifest {
     pub versions: Vec<MinecraftVersion>,
 }
 
+impl VersionManifest {
+    fn resolve_urls(&mut self, manifest_url: &str) {
+        let Ok(base) = Url::parse(manifest_url) else {
+            return;
+        };
+        for version in &mut self.versions {
+            version.url = resolve_url(&base, &version.url);
+            version.details = resolve_url(&base, &version.details);
+        }
+    }
+}
+
+fn resolve_url(base: &Url, url: &str) -> String {
+    base.join(url)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| url.to_owned())
+}
+
 #[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 pub struct LatestVersions {
@@ -93,6 +113,12 @@ pub struct LatestVersions {
     pending: String,
 }
 
+impl LatestVersions {
+    pub fn release(&self) -> &str {
+        &self.release
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Deserialize, Clone, Debug)]
 pub struct MinecraftVersion {
@@ -191,3 +217,41 @@ pub async fn find_lwjgl_url_version(
         mc_version = &version.id
     )))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_relative_details() {
+        let mut manifest = serde_json::from_str::<VersionManifest>(
+            r#"{
+                "latest": {
+                    "old_alpha": "a1.2.6",
+                    "classic_server": "c1.10.1",
+                    "alpha_server": "a0.2.8",
+                    "old_beta": "b1.8.1",
+                    "snapshot": "1.14.4",
+                    "release": "1.14.4",
+                    "pending": "1.15"
+                },
+                "versions": [{
+                    "id": "1.8.9",
+                    "type": "release",
+                    "url": "https://example.com/1.8.9.json",
+                    "releaseTime": "2015-12-03T09:24:39+00:00",
+                    "details": "versions/1.8.9/details.json"
+                }]
+            }"#,
+        )
+        .unwrap();
+        manifest.resolve_urls(LAUNCHER_META_URL);
+
+        let version = &manifest.versions[0];
+        assert_eq!(
+            version.details,
+            "https://ornithemc.net/mc-versions/versions/1.8.9/details.json"
+        );
+        assert_eq!(version.url, "https://example.com/1.8.9.json");
+    }
+}
@@ -19,6 +19,53 @@ pub struct MavenVersion {
     pub version: String,
 }
 
+static PINNED_FLAP_VERSION: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+pub async fn pin_flap_version(version: &str) -> Result<(), InstallerError> {
+    let metadata =
+        net::get_text(format!("{MAVEN_URL}net/ornithemc/flap/maven-metadata.xml")).await?;
+    if !metadata.contains(&format!("<version>{version}</version>")) {
+        return Err(InstallerError::from(t!(
+            "net.error.unknown_flap_version",
+            version = version
+        )));
+    }
+    let _ = PINNED_FLAP_VERSION.set(version.to_owned());
+    Ok(())
+}
+
+fn flap_url(version: &str) -> String {
+    format!("{MAVEN_URL}net/ornithemc/flap/{version}/flap-{version}.jar")
+}
+
+pub async fn get_flap_version() -> Result<MavenVersion, InstallerError> {
+    match PINNED_FLAP_VERSION.get() {
+        Some(version) => Ok(MavenVersion {
+            is_snapshot: false,
+            version: version.clone(),
+        }),
+        None => get_latest_version("flap").await,
+    }
+}
+
+pub async fn get_flap_file() -> Result<Vec<u8>, InstallerError> {
+    match PINNED_FLAP_VERSION.get() {
+        Some(version) => net::get_bytes(flap_url(version)).await,
+        None => get_latest_release_file("flap").await,
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
+pub async fn download_flap(output: &std::path::PathBuf) -> Result<(), InstallerError> {
+    match PINNED_FLAP_VERSION.get() {
+        Some(version) => {
+            crate::net::download_file(&flap_url(version), output, None).await?;
+            Ok(())
+        }
+        None => download_latest_release("flap", output).await,
+    }
+}
+
 pub async fn get_latest_version(artifact: &str) -> Result<MavenVersion, InstallerError> {
     get_json::<MavenVersion>(format!("{}{}", MAVEN_LATEST_VERSION_API_URL, artifact)).await
 }
@@ -27,7 +74,7 @@ pub async fn get_latest_release_file(artifact: &str) -> Result<Vec<u8>, Installe
     net::get_bytes(&format!("{}{}", MAVEN_LATEST_RELEASE_API_URL, artifact)).await
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
 pub async fn download_latest_release(
     artifact: &str,
     output: &std::path::PathBuf,
@@ -35,6 +82,8 @@ pub async fn download_latest_release(
     crate::net::download_file(
         &format!("{}{}", MAVEN_LATEST_RELEASE_API_URL, artifact),
         output,
+        None,
     )
-    .await
+    .await?;
+    Ok(())
 }
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -9,6 +12,11 @@ use super::GameSide;
 
 const META_URL: &str = "https://meta.ornithemc.net";
 
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn probe() -> Result<(), InstallerError> {
+    super::probe(META_URL).await
+}
+
 #[allow(dead_code)]
 #[derive(Deserialize, Clone, Debug)]
 pub struct LoaderVersion {
@@ -27,6 +35,10 @@ impl LoaderVersion {
     pub fn is_stable(&self) -> bool {
         !self.is_beta()
     }
+
+    pub fn build(&self) -> i32 {
+        self.build
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
@@ -56,6 +68,13 @@ impl LoaderType {
             LoaderType::Quilt => "org.quiltmc.quilt-loader",
         }
     }
+
+    pub fn library_prefix(&self) -> String {
+        self.get_maven_uid()
+            .rsplit_once('.')
+            .map(|(group, artifact)| format!("{group}:{artifact}:"))
+            .unwrap_or_default()
+    }
 }
 
 impl GameSide {
@@ -73,6 +92,14 @@ impl GameSide {
     }
 }
 
+static RAW_LAUNCH_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Debugging aid, installations made this way are unsupported. Only `inheritsFrom` is
+/// still set, so the profile finds its vanilla parent.
+pub fn set_raw_launch_json(raw: bool) {
+    RAW_LAUNCH_JSON.store(raw, Ordering::Relaxed);
+}
+
 pub async fn fetch_launch_json(
     side: GameSide,
     intermediary: &IntermediaryVersion,
@@ -102,6 +129,10 @@ pub async fn fetch_launch_json(
     text["inheritsFrom"] =
         Value::String(manifest::vanilla_profile_name(&intermediary.version, generation).await?);
 
+    if RAW_LAUNCH_JSON.load(Ordering::Relaxed) {
+        return Ok((version_id, text));
+    }
+
     let library_upgrades = fetch_profile_libraries(generation, &intermediary.version).await?;
 
     if let Some(libraries) = text["libraries"].as_array_mut() {
@@ -145,6 +176,58 @@ pub async fn fetch_launch_json(
     Ok((version_id, text))
 }
 
+pub fn group_launch_libraries(
+    launch_json: &Value,
+    loader_type: &LoaderType,
+    upgrades: &[ProfileJsonLibrary],
+) -> Vec<(&'static str, Vec<(String, String)>)> {
+    let loader_prefix = loader_type.library_prefix();
+    let mut groups: Vec<(&'static str, Vec<(String, String)>)> = vec![
+        ("loader", Vec::new()),
+        ("intermediary", Vec::new()),
+        ("library upgrades", Vec::new()),
+        ("loader dependencies", Vec::new()),
+    ];
+    for library in launch_json["libraries"].as_array().into_iter().flatten() {
+        let Some(name) = library["name"].as_str() else {
+            continue;
+        };
+        let group = if name.starts_with(&loader_prefix) {
+            0
+        } else if name.starts_with("net.ornithemc:calamus-intermediary:") {
+            1
+        } else if upgrades.iter().any(|upgrade| upgrade.name == name) {
+            2
+        } else {
+            3
+        };
+        let url = library["url"].as_str().unwrap_or_default().to_owned();
+        groups[group].1.push((name.to_owned(), url));
+    }
+    groups.retain(|(_, libraries)| !libraries.is_empty());
+    groups
+}
+
+pub fn launch_json_problems(json: &Value) -> Vec<String> {
+    let mut problems = Vec::new();
+    for key in ["id", "mainClass"] {
+        if json[key].as_str().is_none_or(str::is_empty) {
+            problems.push(format!("missing `{key}`"));
+        }
+    }
+    match json["libraries"].as_array() {
+        Some(libraries) => {
+            for (index, library) in libraries.iter().enumerate() {
+                if library["name"].as_str().is_none() {
+                    problems.push(format!("library {index} has no `name`"));
+                }
+            }
+        }
+        None => problems.push("missing `libraries`".to_owned()),
+    }
+    problems
+}
+
 pub async fn fetch_loader_versions(
     generation: &Option<u32>,
 ) -> Result<HashMap<LoaderType, Vec<LoaderVersion>>, InstallerError> {
@@ -232,3 +315,54 @@ pub async fn fetch_intermediary_generations() -> Result<IntermediaryGenerations,
     )
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reports_launch_json_problems() {
+        let valid = json!({
+            "id": "fabric-loader-0.16.0-1.8.9",
+            "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            "libraries": [{"name": "net.fabricmc:fabric-loader:0.16.0"}]
+        });
+        assert!(launch_json_problems(&valid).is_empty());
+
+        let broken = json!({"id": "x", "libraries": [{"url": "https://example.org"}]});
+        assert_eq!(
+            launch_json_problems(&broken),
+            ["missing `mainClass`", "library 0 has no `name`"]
+        );
+    }
+
+    #[test]
+    fn groups_launch_libraries_by_origin() {
+        let launch_json = json!({"libraries": [
+            {"name": "net.fabricmc:fabric-loader:0.16.0", "url": "https://maven.fabricmc.net/"},
+            {"name": "net.ornithemc:calamus-intermediary:1.8.9", "url": "https://maven.ornithemc.net/releases"},
+            {"name": "org.ow2.asm:asm:9.7", "url": "https://maven.fabricmc.net/"},
+            {"name": "org.apache.logging.log4j:log4j-core:2.17.1", "url": "https://maven.ornithemc.net/releases"}
+        ]});
+        let upgrades = [ProfileJsonLibrary {
+            name: "org.apache.logging.log4j:log4j-core:2.17.1".to_owned(),
+            url: "https://maven.ornithemc.net/releases".to_owned(),
+        }];
+        let groups = group_launch_libraries(&launch_json, &LoaderType::Fabric, &upgrades);
+        let names = groups
+            .iter()
+            .map(|(group, libraries)| (*group, libraries.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("loader", 1),
+                ("intermediary", 1),
+                ("library upgrades", 1),
+                ("loader dependencies", 1)
+            ]
+        );
+    }
+}
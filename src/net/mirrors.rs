@@ -0,0 +1,212 @@
+use std::{collections::HashMap, path::Path, sync::RwLock};
+
+use reqwest::{RequestBuilder, Url};
+
+use crate::errors::InstallerError;
+
+static MIRRORS: RwLock<Option<HashMap<String, Vec<Url>>>> = RwLock::new(None);
+
+/// Loads a mirror list, a json object mapping hosts to arrays of base URLs, e.g.
+/// `{"maven.ornithemc.net": ["https://mirror.example.org/ornithe-maven/"]}`
+pub fn load(file: &Path) -> Result<(), InstallerError> {
+    let list = serde_json::from_slice::<HashMap<String, Vec<String>>>(&std::fs::read(file)?)?;
+    let mut mirrors = HashMap::with_capacity(list.len());
+    for (host, bases) in list {
+        let mut urls = Vec::with_capacity(bases.len());
+        for base in bases {
+            // Without a trailing slash Url::join would replace the last path segment
+            let with_slash = if base.ends_with('/') {
+                base.clone()
+            } else {
+                base.clone() + "/"
+            };
+            let url = Url::parse(&with_slash).map_err(|e| {
+                InstallerError::from(t!(
+                    "net.error.invalid_mirror",
+                    mirror = base,
+                    host = host,
+                    error = e
+                ))
+            })?;
+            urls.push(url);
+        }
+        mirrors.insert(host, urls);
+    }
+    if let Ok(mut lock) = MIRRORS.write() {
+        *lock = Some(mirrors);
+    }
+    Ok(())
+}
+
+pub fn candidates(url: &str) -> Vec<String> {
+    let mut candidates = Vec::new();
+    if let Ok(parsed) = Url::parse(url)
+        && let Some(host) = parsed.host_str()
+        && let Ok(lock) = MIRRORS.read()
+        && let Some(bases) = lock.as_ref().and_then(|mirrors| mirrors.get(host))
+    {
+        candidates.extend(bases.iter().filter_map(|base| rewrite(&parsed, base)));
+    }
+    candidates.push(url.to_owned());
+    candidates
+}
+
+enum Credentials {
+    Token(String),
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+}
+
+impl Credentials {
+    fn from_env() -> Option<Self> {
+        if let Ok(token) = std::env::var("ORNITHE_MAVEN_TOKEN") {
+            Some(Credentials::Token(token))
+        } else if let Ok(username) = std::env::var("ORNITHE_MAVEN_USERNAME") {
+            Some(Credentials::Basic {
+                username,
+                password: std::env::var("ORNITHE_MAVEN_PASSWORD").ok(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Credentials are never sent anywhere but the configured Ornithe maven mirrors,
+/// not even to the public maven itself.
+pub fn authorize(request: RequestBuilder, url: &str) -> RequestBuilder {
+    let Ok(lock) = MIRRORS.read() else {
+        return request;
+    };
+    match lock.as_ref() {
+        Some(mirrors) => authorize_with(request, url, mirrors, Credentials::from_env()),
+        None => request,
+    }
+}
+
+fn authorize_with(
+    request: RequestBuilder,
+    url: &str,
+    mirrors: &HashMap<String, Vec<Url>>,
+    credentials: Option<Credentials>,
+) -> RequestBuilder {
+    let Ok(url) = Url::parse(url) else {
+        return request;
+    };
+    if !is_private_maven(&url, mirrors) {
+        return request;
+    }
+    match credentials {
+        Some(Credentials::Token(token)) => request.bearer_auth(token),
+        Some(Credentials::Basic { username, password }) => request.basic_auth(username, password),
+        None => request,
+    }
+}
+
+fn is_private_maven(url: &Url, mirrors: &HashMap<String, Vec<Url>>) -> bool {
+    let Some(maven_host) = Url::parse(super::maven::MAVEN_URL)
+        .ok()
+        .and_then(|maven| maven.host_str().map(|host| host.to_owned()))
+    else {
+        return false;
+    };
+    if url.host_str() == Some(&maven_host) {
+        return false;
+    }
+    mirrors.get(&maven_host).into_iter().flatten().any(|base| {
+        base.host_str() == url.host_str()
+            && base.port_or_known_default() == url.port_or_known_default()
+    })
+}
+
+fn rewrite(url: &Url, base: &Url) -> Option<String> {
+    let mut path = url.path().trim_start_matches('/').to_owned();
+    if let Some(query) = url.query() {
+        path += "?";
+        path += query;
+    }
+    base.join(&path).ok().map(|url| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_onto_mirror_base() {
+        let url =
+            Url::parse("https://maven.ornithemc.net/releases/net/ornithemc/a.jar?x=1").unwrap();
+        let base = Url::parse("https://mirror.example.org/ornithe/").unwrap();
+        assert_eq!(
+            rewrite(&url, &base).unwrap(),
+            "https://mirror.example.org/ornithe/releases/net/ornithemc/a.jar?x=1"
+        );
+    }
+
+    fn private_mirrors() -> HashMap<String, Vec<Url>> {
+        HashMap::from([(
+            "maven.ornithemc.net".to_owned(),
+            vec![Url::parse("https://private.example.org/maven/").unwrap()],
+        )])
+    }
+
+    fn authorization(url: &str, credentials: Option<Credentials>) -> Option<String> {
+        let request = reqwest::Client::new().get(url);
+        authorize_with(request, url, &private_mirrors(), credentials)
+            .build()
+            .unwrap()
+            .headers()
+            .get(reqwest::header::AUTHORIZATION)
+            .map(|value| value.to_str().unwrap().to_owned())
+    }
+
+    #[test]
+    fn only_maven_mirrors_are_private() {
+        let mirrors = private_mirrors();
+        let private = |url: &str| is_private_maven(&Url::parse(url).unwrap(), &mirrors);
+        assert!(private(
+            "https://private.example.org/maven/net/ornithemc/a.jar"
+        ));
+        assert!(!private(
+            "https://private.example.org:8443/maven/net/ornithemc/a.jar"
+        ));
+        assert!(!private(
+            "https://maven.ornithemc.net/releases/net/ornithemc/a.jar"
+        ));
+        assert!(!private("https://meta.ornithemc.net/v3/versions"));
+    }
+
+    #[test]
+    fn sends_a_bearer_token_to_private_mirrors() {
+        let token = || Some(Credentials::Token("secret".to_owned()));
+        assert_eq!(
+            authorization("https://private.example.org/maven/a.jar", token()).as_deref(),
+            Some("Bearer secret")
+        );
+        assert_eq!(
+            authorization("https://maven.ornithemc.net/releases/a.jar", token()),
+            None
+        );
+    }
+
+    #[test]
+    fn sends_basic_auth_to_private_mirrors() {
+        let basic = || {
+            Some(Credentials::Basic {
+                username: "user".to_owned(),
+                password: Some("pass".to_owned()),
+            })
+        };
+        // base64 of "user:pass"
+        assert_eq!(
+            authorization("https://private.example.org/maven/a.jar", basic()).as_deref(),
+            Some("Basic dXNlcjpwYXNz")
+        );
+        assert_eq!(
+            authorization("https://evil.example.com/a.jar", basic()),
+            None
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use reqwest::Client;
+use reqwest::{Client, StatusCode, header::CONTENT_TYPE};
 use serde::de::DeserializeOwned;
 
 use crate::errors::InstallerError;
@@ -6,36 +6,276 @@ use crate::errors::InstallerError;
 pub mod manifest;
 pub mod maven;
 pub mod meta;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mirrors;
 
 static CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
-    reqwest::Client::builder()
-        .user_agent(crate::USER_AGENT)
-        .build()
-        .unwrap()
+    let builder = reqwest::Client::builder().user_agent(crate::USER_AGENT);
+    #[cfg(not(target_arch = "wasm32"))]
+    let builder = if http1_only() {
+        log::info!("Forcing HTTP/1.1 for all requests");
+        builder.http1_only()
+    } else {
+        builder
+    };
+    builder.build().unwrap()
 });
+
+#[cfg(not(target_arch = "wasm32"))]
+static HTTP1_ONLY: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// For proxies that break HTTP/2. Only has an effect before the first request is made.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_http1_only(http1_only: bool) {
+    HTTP1_ONLY.store(http1_only, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// `ORNITHE_HTTP1=1` also reaches the GUI.
+#[cfg(not(target_arch = "wasm32"))]
+fn http1_only() -> bool {
+    HTTP1_ONLY.load(std::sync::atomic::Ordering::Relaxed)
+        || std::env::var("ORNITHE_HTTP1").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
 #[cfg(target_arch = "wasm32")]
 pub static UNCONFIGURED_CLIENT: std::sync::LazyLock<reqwest::Client> =
     std::sync::LazyLock::new(|| reqwest::Client::builder().build().unwrap());
 
 #[cfg(not(target_arch = "wasm32"))]
-pub async fn download_file(url: &str, output: &std::path::PathBuf) -> Result<(), InstallerError> {
-    let bytes = get_bytes(url).await?;
+static FILE_TIMEOUT_SECS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
+pub fn set_file_timeout(timeout: std::time::Duration) {
+    FILE_TIMEOUT_SECS.store(timeout.as_secs(), std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Unless overridden this allows for roughly 100 KiB/s on top of a fixed allowance
+/// for connecting.
+#[cfg(not(target_arch = "wasm32"))]
+fn file_timeout(expected_size: Option<u64>) -> std::time::Duration {
+    let secs = FILE_TIMEOUT_SECS.load(std::sync::atomic::Ordering::Relaxed);
+    if secs > 0 {
+        return std::time::Duration::from_secs(secs);
+    }
+    let size = expected_size.unwrap_or(10 * 1024 * 1024);
+    std::time::Duration::from_secs(30 + size / (100 * 1024))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static TEMP_DIR: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_temp_dir(dir: std::path::PathBuf) {
+    let _ = TEMP_DIR.set(dir);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn temp_file() -> Result<std::path::PathBuf, InstallerError> {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let dir = TEMP_DIR.get().cloned().unwrap_or_else(std::env::temp_dir);
+    std::fs::create_dir_all(&dir)?;
+    let id = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    Ok(dir.join(format!(
+        "ornithe-installer-{}-{id}.part",
+        std::process::id()
+    )))
+}
+
+fn get(client: &Client, url: &str) -> reqwest::RequestBuilder {
+    let request = client.get(url);
+    #[cfg(not(target_arch = "wasm32"))]
+    let request = mirrors::authorize(request, url);
+    request
+}
+
+async fn with_mirrors<T, F, Fut>(url: &str, request: F) -> Result<T, InstallerError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<T, InstallerError>>,
+{
+    let result = try_mirrors(url, request).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    if result.is_err()
+        && let Some(blocked) = check_sinkholed(url).await
+    {
+        return Err(blocked);
+    }
+    result
+}
+
+async fn try_mirrors<T, F, Fut>(url: &str, mut request: F) -> Result<T, InstallerError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<T, InstallerError>>,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    let candidates = mirrors::candidates(url);
+    #[cfg(target_arch = "wasm32")]
+    let candidates = vec![url.to_owned()];
+
+    let count = candidates.len();
+    let mut last_error = None;
+    for (index, candidate) in candidates.into_iter().enumerate() {
+        match request(candidate.clone()).await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if index + 1 < count {
+                    log::warn!("{candidate} failed ({}), trying the next mirror", e.0);
+                }
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| InstallerError(url.to_owned())))
+}
+
+const BLOCK_PAGE_MARKERS: &[&str] = &[
+    "pi-hole",
+    "adguard",
+    "nextdns",
+    "opendns",
+    "blocked by your network",
+    "this site is blocked",
+    "website blocked",
+];
+
+fn is_block_page(text: &str) -> bool {
+    let text = text.to_lowercase();
+    BLOCK_PAGE_MARKERS
+        .iter()
+        .any(|marker| text.contains(marker))
+}
+
+fn blocked_host(url: &str) -> InstallerError {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_else(|| url.to_owned());
+    InstallerError::from(t!("net.error.blocked", host = host))
+}
+
+/// DNS sinkholes such as Pi-hole answer for blocked hosts with null or loopback addresses.
+#[cfg(not(target_arch = "wasm32"))]
+async fn check_sinkholed(url: &str) -> Option<InstallerError> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    // A mirror deliberately pointed at this machine is not a block
+    let host = parsed.domain().filter(|host| *host != "localhost")?;
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<_> = tokio::net::lookup_host((host, port)).await.ok()?.collect();
+    let sinkholed = !addrs.is_empty()
+        && addrs
+            .iter()
+            .all(|addr| addr.ip().is_unspecified() || addr.ip().is_loopback());
+    sinkholed.then(|| blocked_host(url))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn download_file(
+    url: &str,
+    output: &std::path::PathBuf,
+    expected_size: Option<u64>,
+) -> Result<String, InstallerError> {
+    with_mirrors(url, |url| download_file_from(url, output, expected_size)).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn download_file_from(
+    url: String,
+    output: &std::path::PathBuf,
+    expected_size: Option<u64>,
+) -> Result<String, InstallerError> {
+    use std::io::Write;
+
+    let url = url.as_str();
+
+    let timeout = file_timeout(expected_size);
+    let map_timeout = |e: reqwest::Error| {
+        if e.is_timeout() {
+            InstallerError::from(t!(
+                "net.error.download_timed_out",
+                url = url,
+                seconds = timeout.as_secs()
+            ))
+        } else {
+            InstallerError::from(e)
+        }
+    };
+    let mut response = get(&CLIENT, url)
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(map_timeout)?;
+    check_file_response(url, &response)?;
     if let Some(parent) = output.parent()
         && !std::fs::exists(parent)?
     {
         std::fs::create_dir_all(parent)?;
     }
+    let part = temp_file()?;
+    let mut file = std::fs::File::create(&part)?;
+    let mut hasher = sha1_smol::Sha1::new();
+    let streamed = async {
+        while let Some(chunk) = response.chunk().await.map_err(map_timeout)? {
+            hasher.update(&chunk);
+            file.write_all(&chunk)?;
+        }
+        Ok::<_, InstallerError>(())
+    }
+    .await;
+    drop(file);
+    if let Err(e) = streamed {
+        let _ = std::fs::remove_file(&part);
+        return Err(e);
+    }
+
     if std::fs::exists(output).unwrap_or(false) {
         std::fs::remove_file(output)?;
     }
-    std::fs::write(output, bytes)?;
+    // The temp directory may be on another filesystem, where renaming fails
+    if std::fs::rename(&part, output).is_err() {
+        let copied = std::fs::copy(&part, output);
+        let _ = std::fs::remove_file(&part);
+        copied?;
+    }
+
+    Ok(hasher.digest().to_string())
+}
 
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn probe(url: &str) -> Result<(), InstallerError> {
+    let response = CLIENT
+        .head(url)
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await;
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => return Err(check_sinkholed(url).await.unwrap_or_else(|| e.into())),
+    };
+    if response.status().is_server_error() {
+        return Err(unexpected_response(url, response.status()));
+    }
     Ok(())
 }
 
+pub fn sha1(bytes: &[u8]) -> String {
+    sha1_smol::Sha1::from(bytes).digest().to_string()
+}
+
+pub fn verify_sha1(name: &str, expected: &str, actual: &str) -> Result<(), InstallerError> {
+    if expected.eq_ignore_ascii_case(actual) {
+        return Ok(());
+    }
+    Err(InstallerError::from(t!(
+        "net.error.hash_mismatch",
+        name = name,
+        expected = expected,
+        actual = actual
+    )))
+}
+
 pub async fn get_json<T>(url: impl Into<String>) -> Result<T, InstallerError>
 where
-    T: DeserializeOwned,
+    T: DeserializeOwned + Send + 'static,
 {
     get_json_client(&CLIENT, url).await
 }
@@ -44,10 +284,175 @@ pub async fn get_json_client<T>(
     client: &Client,
     url: impl Into<String>,
 ) -> Result<T, InstallerError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let url = url.into();
+    #[cfg(not(target_arch = "wasm32"))]
+    return get_json_cached(client, url).await;
+    #[cfg(target_arch = "wasm32")]
+    with_mirrors(&url, |candidate| async move {
+        let (status, content_type, text) = fetch_text(client, &candidate).await?;
+        parse_json(candidate, status, content_type, text).await
+    })
+    .await
+    .map(|(value, _)| value)
+}
+
+/// Only falls back to the cache when the servers can't be reached at all. Anything they
+/// did answer, e.g. a captive portal page, is still reported.
+#[cfg(not(target_arch = "wasm32"))]
+async fn get_json_cached<T>(client: &Client, url: String) -> Result<T, InstallerError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    if crate::cache::offline() {
+        let Some(text) = crate::cache::cached_metadata(&url) else {
+            return Err(InstallerError::from(t!("net.error.not_cached", url = url)));
+        };
+        return parse_json(url, StatusCode::OK, String::new(), text)
+            .await
+            .map(|(value, _)| value);
+    }
+    // Whether the last mirror tried could not be connected to or timed out
+    let unreachable = AtomicBool::new(false);
+    let result = try_mirrors(&url, |candidate| {
+        let unreachable = &unreachable;
+        async move {
+            let response = fetch_text(client, &candidate).await;
+            unreachable.store(
+                response
+                    .as_ref()
+                    .is_err_and(|e| e.is_connect() || e.is_timeout()),
+                Ordering::Relaxed,
+            );
+            let (status, content_type, text) = response?;
+            parse_json(candidate, status, content_type, text).await
+        }
+    })
+    .await;
+    let e = match result {
+        Ok((value, text)) => {
+            crate::cache::store_metadata(&url, &text);
+            return Ok(value);
+        }
+        Err(e) => e,
+    };
+    if let Some(blocked) = check_sinkholed(&url).await {
+        return Err(blocked);
+    }
+    match crate::cache::cached_metadata(&url).filter(|_| unreachable.load(Ordering::Relaxed)) {
+        Some(text) => {
+            log::warn!("{}, using the cached response of {url}", e.0);
+            parse_json(url, StatusCode::OK, String::new(), text)
+                .await
+                .map(|(value, _)| value)
+        }
+        None => Err(e),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const LARGE_RESPONSE_BYTES: usize = 256 * 1024;
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    static STATUS: tokio::sync::mpsc::UnboundedSender<(f32, String)>;
+}
+
+/// The messages carry no progress, so this is meant for loading the version metadata
+/// before anything else has been reported.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn with_status<F: Future>(
+    sender: tokio::sync::mpsc::UnboundedSender<(f32, String)>,
+    future: F,
+) -> F::Output {
+    STATUS.scope(sender, future).await
+}
+
+async fn fetch_text(client: &Client, url: &str) -> reqwest::Result<(StatusCode, String, String)> {
+    let response = get(client, url).send().await?;
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    // reqwest decompresses responses before handing them out and drops their
+    // Content-Length, so for compressed ones only the decoded size is known
+    let wire_size = response.content_length();
+    let text = response.text().await?;
+    match wire_size {
+        Some(wire_size) => log::debug!("{url}: {wire_size} bytes transferred"),
+        None => log::debug!(
+            "{url}: {} bytes decoded (compressed, transfer size not reported)",
+            text.len()
+        ),
+    }
+    Ok((status, content_type, text))
+}
+
+async fn parse_json<T>(
+    url: String,
+    status: StatusCode,
+    content_type: String,
+    text: String,
+) -> Result<(T, String), InstallerError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    // Big manifests take a moment to deserialize, which should not stall the runtime
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if text.len() >= LARGE_RESPONSE_BYTES {
+            let message = t!("net.info.parsing", url = url).to_string();
+            if STATUS
+                .try_with(|sender| sender.send((0.0, message.clone())))
+                .is_err()
+            {
+                log::info!("{message}");
+            }
+        }
+        tokio::task::spawn_blocking(move || {
+            parse_json_response(&url, status, &content_type, &text).map(|value| (value, text))
+        })
+        .await
+        .map_err(|e| InstallerError(e.to_string()))?
+    }
+    #[cfg(target_arch = "wasm32")]
+    parse_json_response(&url, status, &content_type, &text).map(|value| (value, text))
+}
+
+fn parse_json_response<T>(
+    url: &str,
+    status: StatusCode,
+    content_type: &str,
+    text: &str,
+) -> Result<T, InstallerError>
 where
     T: DeserializeOwned,
 {
-    Ok(client.get(url.into()).send().await?.json::<T>().await?)
+    // Captive portals and proxies tend to answer with an html page instead of the
+    // json we asked for, which serde would only report as "expected value at line 1 column 1".
+    if !status.is_success() || content_type.contains("html") || text.trim_start().starts_with('<') {
+        if is_block_page(text) {
+            return Err(blocked_host(url));
+        }
+        return Err(unexpected_response(url, status));
+    }
+    serde_json::from_str::<T>(text)
+        .map_err(|e| InstallerError::from(t!("net.error.invalid_json", url = url, error = e)))
+}
+
+fn unexpected_response(url: &str, status: StatusCode) -> InstallerError {
+    InstallerError::from(t!(
+        "net.error.unexpected_response",
+        url = url,
+        status = status
+    ))
 }
 
 #[allow(unused)]
@@ -55,11 +460,17 @@ pub async fn get_text(url: impl Into<String>) -> Result<String, InstallerError>
     get_text_client(&CLIENT, url).await
 }
 
+#[allow(unused)]
 pub async fn get_text_client(
     client: &Client,
     url: impl Into<String>,
 ) -> Result<String, InstallerError> {
-    Ok(client.get(url.into()).send().await?.text().await?)
+    with_mirrors(&url.into(), |url| async move {
+        let response = get(client, &url).send().await?;
+        check_file_response(&url, &response)?;
+        Ok(response.text().await?)
+    })
+    .await
 }
 
 pub async fn get_bytes(url: impl Into<String>) -> Result<Vec<u8>, InstallerError> {
@@ -70,9 +481,27 @@ pub async fn get_bytes_client(
     client: &Client,
     url: impl Into<String>,
 ) -> Result<Vec<u8>, InstallerError> {
-    Ok(client.get(url.into()).send().await?.bytes().await?.to_vec())
+    with_mirrors(&url.into(), |url| async move {
+        let response = get(client, &url).send().await?;
+        check_file_response(&url, &response)?;
+        Ok(response.bytes().await?.to_vec())
+    })
+    .await
+}
+
+fn check_file_response(url: &str, response: &reqwest::Response) -> Result<(), InstallerError> {
+    let is_html = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("html"));
+    if !response.status().is_success() || is_html {
+        return Err(unexpected_response(url, response.status()));
+    }
+    Ok(())
 }
 
+#[derive(Clone)]
 pub enum GameSide {
     Client,
     Server,
@@ -93,3 +522,16 @@ impl GameSide {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_block_pages() {
+        assert!(is_block_page(
+            "<html><title>Website Blocked</title><body>Pi-hole</body></html>"
+        ));
+        assert!(!is_block_page("<html><body>502 Bad Gateway</body></html>"));
+    }
+}
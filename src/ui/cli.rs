@@ -1,15 +1,21 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use clap::{ArgMatches, Command, arg, command, value_parser};
+use clap::{ArgAction, ArgMatches, Command, arg, command, value_parser};
 #[cfg(not(target_arch = "wasm32"))]
 use indicatif::{ProgressBar, ProgressStyle};
 use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
 
+#[cfg(feature = "mmc")]
+use crate::actions::prism_pack::{ManagedPack, OnExists, WindowSettings};
 use crate::{
+    actions::client::{InstallResult, ProfileChange},
     errors::InstallerError,
     net::{
         GameSide,
-        manifest::MinecraftVersion,
+        manifest::{LatestVersions, MinecraftVersion},
         meta::{IntermediaryVersion, LoaderType, LoaderVersion},
     },
 };
@@ -25,6 +31,31 @@ pub async fn run() {
         .arg_required_else_help(true)
         .name("Ornithe Installer")
         .after_help("Additional arguments are available for subcommands. See their help pages for details.")
+        .arg(arg!(--strict "Treat warnings as errors").global(true))
+        .arg(arg!(--"warnings-json" <FILE> "Write warnings and errors to this file as JSON lines")
+            .value_parser(value_parser!(PathBuf))
+            .global(true))
+        .arg(arg!(--progress <MODE> "How to report progress: a progress bar, or one JSON object per event on stderr")
+            .default_value("bar")
+            .value_parser(["bar", "json"])
+            .global(true))
+        .arg(arg!(--"raw-launch-json" "Advanced, unsupported: install the loader launch json exactly as the meta server returns it")
+            .hide(true)
+            .global(true))
+        .arg(arg!(--pretty "Pretty-print all generated json files").global(true))
+        .arg(arg!(--"line-endings" <STYLE> "Line endings of generated text files (the jar manifest always uses CRLF)")
+            .default_value("native")
+            .ignore_case(true)
+            .value_parser(["lf", "crlf", "native"])
+            .global(true))
+        .arg(arg!(--"mirror-list" <FILE> "JSON file mapping download hosts to mirror base URLs, tried in order")
+            .value_parser(value_parser!(PathBuf))
+            .global(true))
+        .arg(arg!(--http1 "Only use HTTP/1.1, for proxies that stall or reset HTTP/2 connections (also ORNITHE_HTTP1=1)")
+            .global(true))
+        .arg(arg!(--"temp-dir" <DIR> "Directory for files being downloaded (default: TMPDIR or the OS temp directory)")
+            .value_parser(value_parser!(PathBuf))
+            .global(true))
         .subcommand(
             add_arguments(Command::new("client")
                 .about("Client installation for the official launcher")
@@ -38,9 +69,40 @@ pub async fn run() {
                     arg!(-p --"generate-profile" <VALUE> "Whether to generate a launch profile")
                     .default_value("true")
                         .value_parser(value_parser!(bool)),
-                )),
+                )
+                .arg(arg!(--"repair-profiles" "Back up and reset launcher_profiles.json if its profile list is malformed").global(true))
+                .arg(arg!(--"set-default" "Make the generated profile the launcher's selected profile").global(true))
+                .arg(arg!(--"release-time-mtime" "Date the written launch jsons to the version's release time, so reinstalls do not churn synced game directories"))
+                .arg(arg!(--portable "Treat the directory as a portable launcher root, creating it and its launcher_profiles.json if needed"))
+                .arg(arg!(--"create-dir" "Create the directory and an empty launcher_profiles.json if the directory does not exist"))
+                .arg(arg!(--"download-assets" "Download the game assets so the client can be started offline"))
+                .arg(arg!(--"asset-concurrency" <COUNT> "Number of asset objects to download at once")
+                    .default_value("8")
+                    .value_parser(value_parser!(u16).range(1..))
+                    .requires("download-assets"))
+                .arg(arg!(--"assets-url" <URL> "Base URL to download asset objects from (default: https://resources.download.minecraft.net/)")
+                    .env("ORNITHE_ASSETS_URL"))
+                .arg(arg!(--"asset-index-url" <URL> "Base URL to download the asset index from, keeping the path of Mojang's index URL")
+                    .env("ORNITHE_ASSET_INDEX_URL"))
+                .arg(arg!(--parallel <COUNT> "Number of versions to install at once when installing several")
+                    .default_value("1")
+                    .value_parser(value_parser!(u16).range(1..)))
+                .subcommand_negates_reqs(true)
+                .subcommand(add_installed_profile_arguments(Command::new("repair-profile")
+                    .about("Recreate the launcher profile for an installed Ornithe version without touching its files")))
+                .subcommand(add_installed_profile_arguments(Command::new("uninstall")
+                    .about("Remove installed Ornithe versions and their launcher profiles")
+                    .arg(arg!(--"dry-run" "Only list what would be removed"))
+                    .arg(arg!(-y --yes "Do not ask for confirmation"))))
+                .subcommand(add_installed_profile_arguments(Command::new("verify")
+                    .about("Check that an installed Ornithe version's launch metadata is intact, without launching the game"))))
+                .mut_arg("minecraft-version", |arg| {
+                    arg.help("Minecraft version(s) to use, separated by commas to install several")
+                        .action(ArgAction::Append)
+                        .value_delimiter(',')
+                }),
         )
-        .subcommand(
+        .subcommands(cfg!(feature = "mmc").then(||
             add_arguments(Command::new("prism")
                 .long_flag("prism")
                 .alias("mmc")
@@ -55,10 +117,31 @@ pub async fn run() {
                     .default_value("true").value_parser(value_parser!(bool)))
                 .arg(arg!(-c --"copy-profile-path" <VALUE> "Whether to copy the path of the generated profile to the clipboard")
                     .default_value("false").value_parser(value_parser!(bool))
-            .value_parser(value_parser!(bool)))),
-        )
-        .subcommand(
-            add_arguments(Command::new("server")
+            .value_parser(value_parser!(bool)))
+                .arg(arg!(--"on-exists" <ACTION> "What to do if the instance directory already exists (backup moves it into .ornithe-backups with a timestamp suffix)")
+                    .default_value("abort")
+                    .ignore_case(true)
+                    .value_parser(["abort", "overwrite", "backup"]))
+                .arg(arg!(--"managed-pack" <ID> "Mark the instance as a Prism managed pack with the given pack id"))
+                .arg(arg!(--"managed-pack-type" <TYPE> "The platform the managed pack is hosted on")
+                    .default_value("modrinth")
+                    .ignore_case(true)
+                    .value_parser(["modrinth", "flame"])
+                    .requires("managed-pack"))
+                .arg(arg!(--"window-width" <PIXELS> "Initial game window width for the instance")
+                    .value_parser(value_parser!(u32).range(1..)))
+                .arg(arg!(--"window-height" <PIXELS> "Initial game window height for the instance")
+                    .value_parser(value_parser!(u32).range(1..)))
+                .arg(arg!(--fullscreen "Start the instance's game window maximized"))
+                .arg(arg!(--"shared-components" "Write all instances into one zip that stores identical patches only once, with a components.json per instance referencing them")))
+                .mut_arg("minecraft-version", |arg| {
+                    arg.help("Minecraft version(s) to use, separated by commas to generate several instances")
+                        .action(ArgAction::Append)
+                        .value_delimiter(',')
+                }),
+        ))
+        .subcommands(cfg!(feature = "server").then(||
+            add_server_env_vars(add_arguments(Command::new("server")
                 .about("Server installation")
                 .long_flag("server")
                 .arg(
@@ -71,11 +154,26 @@ pub async fn run() {
                     .visible_alias("download")
                     .default_value("true").value_parser(value_parser!(bool))
                 )
-                .subcommand(Command::new("run").about("Install and run the server")
+                .arg(arg!(--"server-launcher-jar" <PATH> "Use this server launcher jar instead of the embedded one")
+                    .value_parser(value_parser!(PathBuf))
+                    .hide(true)
+                    .global(true))
+                .arg(arg!(--"main-class" <CLASS> "Start this class instead of the loader's server launcher (unsupported)")
+                    .hide(true)
+                    .global(true))
+                .arg(arg!(--sequential "Download libraries one at a time instead of in parallel, e.g. on restricted networks"))
+                .arg(arg!(--"pack-libraries" "After installing, also bundle the libraries directory into a portable libraries.zip"))
+                .arg(arg!(--"timeout-per-file" <SECONDS> "Time allowed for each individual download (default: derived from the file size)")
+                    .value_parser(value_parser!(u64).range(1..)))
+                .subcommand_negates_reqs(true)
+                .subcommand(Command::new("run").about("Install and run the server, by default the version already installed")
                     .arg(arg!(--args <ARGS> "Java arguments to pass to the server (before the server jar)"))
-                    .arg(arg!(--java <PATH> "The java binary to use to run the server").value_parser(value_parser!(PathBuf))
+                    .arg(arg!(--"post-args" <ARGS> "Arguments to pass to the server itself (after the server jar)"))
+                    .arg(arg!(--gui "Do not pass nogui, so the server opens its own window"))
+                    .arg(arg!(--java <PATH> "The java binary to use to run the server, or 'auto' to pick an installed one matching the version"))
+                    .arg(arg!(--"accept-eula" "Accept the Minecraft EULA (https://aka.ms/MinecraftEULA) by writing the server's eula.txt")
                 )),
-        ))
+        ))))
         .subcommand(
             add_gen_argument(Command::new("game-versions"))
             .alias("minecraft-versions")
@@ -83,13 +181,15 @@ pub async fn run() {
             .long_flag_alias("list-minecraft-versions")
                 .about("List supported game versions.")
                 .arg(arg!(-s --"show-snapshots" "Include snapshot versions"))
-                .arg(arg!(--"show-historical" "Include historical versions")),
+                .arg(arg!(--"show-historical" "Include historical versions"))
+                .arg(arg!(--"show-dates" "Include each version's release date")),
         )
         .subcommand(
             add_gen_argument(Command::new("loader-versions")
             .long_flag("list-loader-versions")
             .about("List available loader versions.")
                 .arg(arg!(-b --"show-betas" "Include beta versions"))
+                .arg(min_build_argument())
                 .arg(arg!(--"loader-type" <TYPE> "Loader type to use")
                 .default_value("fabric")
                 .ignore_case(true)
@@ -99,6 +199,60 @@ pub async fn run() {
         .long_flag("intermediary-generations")
         .about("List the latest & stable intermediary (Calamus) generations")
     );
+    #[cfg(not(target_arch = "wasm32"))]
+    let command = command.subcommand(
+        Command::new("diff")
+            .about("Compare the launch jsons of two installed profiles")
+            .arg(
+                arg!(-d --dir <DIR> "Minecraft directory containing the profiles")
+                    .default_value(super::dot_minecraft_location())
+                    .value_parser(value_parser!(PathBuf)),
+            )
+            .arg(arg!(<PROFILE_A> "The first profile"))
+            .arg(arg!(<PROFILE_B> "The profile to compare it with")),
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    let command = command.subcommand(
+        Command::new("cache")
+            .about("Inspect or clear the installer's caches")
+            .subcommand_required(true)
+            .subcommand(Command::new("info").about("Show the size and entry count of each cache"))
+            .subcommand(Command::new("clear").about("Delete the installer's cache directory")),
+    );
+    #[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
+    let command = command.subcommand(
+        add_version_arguments(Command::new("list-libraries"))
+            .long_flag("list-libraries")
+            .about("List the libraries an installation would download, without downloading them")
+            .arg(
+                arg!(--side <SIDE> "The side whose launch json to list")
+                    .default_value("server")
+                    .ignore_case(true)
+                    .value_parser(["client", "server"]),
+            ),
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    let command = command.subcommand(
+        add_version_arguments(Command::new("deps"))
+            .about("Show everything Ornithe adds for a version and loader as a tree, with versions and sources")
+            .arg(
+                arg!(--side <SIDE> "The side to show the dependencies of")
+                    .default_value("client")
+                    .ignore_case(true)
+                    .value_parser(["client", "server"]),
+            ),
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    let command = command.subcommand(
+        add_version_arguments(Command::new("validate-meta"))
+            .about("Fetch every metadata response an installation would use and check that it parses, without installing")
+            .arg(
+                arg!(--side <SIDE> "The side to validate the launch json of")
+                    .default_value("client")
+                    .ignore_case(true)
+                    .value_parser(["client", "server"]),
+            ),
+    );
 
     #[cfg(target_arch = "wasm32")]
     {
@@ -159,7 +313,25 @@ pub async fn run() {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    match parse(command.get_matches()).await {
+    let matches = command.get_matches();
+    #[cfg(not(target_arch = "wasm32"))]
+    let diagnostics = matches
+        .get_one::<PathBuf>("warnings-json")
+        .cloned()
+        .map(|file| (file, crate::actions::subscribe_diagnostics()));
+    #[cfg(not(target_arch = "wasm32"))]
+    let result = parse(matches).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some((file, receiver)) = diagnostics {
+        if let Err(e) = &result {
+            crate::actions::emit_diagnostic(crate::actions::DiagnosticLevel::Error, &e.0);
+        }
+        if let Err(e) = write_diagnostics(&file, receiver) {
+            println!("Failed to write {}: {}", file.display(), e.0);
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    match result {
         Ok(r) => {
             if r == InstallationResult::Installed {
                 println!("Installation complete!");
@@ -176,7 +348,51 @@ pub async fn run() {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn write_diagnostics(
+    file: &Path,
+    mut receiver: tokio::sync::mpsc::UnboundedReceiver<crate::actions::Diagnostic>,
+) -> Result<(), InstallerError> {
+    let mut out = String::new();
+    while let Ok(diagnostic) = receiver.try_recv() {
+        out += &serde_json::to_string(&diagnostic)?;
+        out += "\n";
+    }
+    std::fs::write(file, out)?;
+    Ok(())
+}
+
 async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError> {
+    crate::actions::set_strict(matches.get_flag("strict"));
+    crate::actions::set_pretty(matches.get_flag("pretty"));
+    crate::actions::set_crlf(
+        match matches
+            .get_one::<String>("line-endings")
+            .unwrap()
+            .to_lowercase()
+            .as_str()
+        {
+            "crlf" => true,
+            "lf" => false,
+            _ => cfg!(windows),
+        },
+    );
+    if matches.get_flag("raw-launch-json") {
+        crate::net::meta::set_raw_launch_json(true);
+        println!(
+            "Using raw launch jsons: the intermediary is not rewritten and library upgrades are skipped. This is meant for debugging only and is not supported."
+        );
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(file) = matches.get_one::<PathBuf>("mirror-list") {
+        crate::net::mirrors::load(file)?;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(dir) = matches.get_one::<PathBuf>("temp-dir") {
+        crate::net::set_temp_dir(dir.clone());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    crate::net::set_http1_only(matches.get_flag("http1"));
     if matches
         .subcommand_matches("intermediary-generations")
         .is_some()
@@ -196,6 +412,101 @@ async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError
         }
         return Ok(InstallationResult::NotInstalled);
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(matches) = matches.subcommand_matches("cache") {
+        let dir = crate::cache::cache_dir()?;
+        if matches.subcommand_matches("clear").is_some() {
+            let freed = crate::cache::clear()?;
+            println!(
+                "Cleared {} ({} entries, {} freed)",
+                dir.display(),
+                crate::format::format_count(freed.entries as u64),
+                crate::cache::format_size(freed.bytes)
+            );
+        } else {
+            println!("Cache directory: {}", dir.display());
+            let entries = crate::cache::info()?;
+            if entries.is_empty() {
+                println!("The cache is empty.");
+            }
+            for (name, stats) in entries {
+                println!(
+                    "{name}: {} entries, {}",
+                    crate::format::format_count(stats.entries as u64),
+                    crate::cache::format_size(stats.bytes)
+                );
+            }
+        }
+        return Ok(InstallationResult::NotInstalled);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(matches) = matches.subcommand_matches("diff") {
+        let diff = crate::actions::diff::diff_profiles(
+            matches.get_one::<PathBuf>("dir").unwrap(),
+            matches.get_one::<String>("PROFILE_A").unwrap(),
+            matches.get_one::<String>("PROFILE_B").unwrap(),
+        )?;
+        print!("{diff}");
+        return Ok(InstallationResult::NotInstalled);
+    }
+    #[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
+    if let Some(matches) = matches.subcommand_matches("list-libraries") {
+        let side = match matches
+            .get_one::<String>("side")
+            .unwrap()
+            .to_lowercase()
+            .as_str()
+        {
+            "client" => GameSide::Client,
+            _ => GameSide::Server,
+        };
+        let (_, intermediary, info) = get_minecraft_version(
+            matches,
+            matches.get_one::<String>("minecraft-version").unwrap(),
+            side.clone(),
+        )
+        .await?;
+        let loader_type = get_loader_type(matches)?;
+        let all_loader_versions =
+            crate::net::meta::fetch_loader_versions(&info.calamus_generation).await?;
+        let loader_version =
+            get_loader_version(matches, all_loader_versions.get(&loader_type).unwrap())?;
+        let (profile_name, launch_json) = crate::net::meta::fetch_launch_json(
+            side,
+            &intermediary,
+            &loader_type,
+            &loader_version,
+            &info.calamus_generation,
+        )
+        .await?;
+        let libraries = crate::actions::server::library_downloads(&launch_json)?;
+        println!("Libraries of {profile_name}:");
+        let mut total = 0;
+        for (name, url, size) in &libraries {
+            let size = match size {
+                Some(size) => {
+                    total += size;
+                    format!("{} bytes", crate::format::format_count(*size))
+                }
+                None => "unknown size".to_owned(),
+            };
+            println!("{name}\t{url}\t{size}");
+        }
+        println!(
+            "{} libraries, {} bytes known",
+            crate::format::format_count(libraries.len() as u64),
+            crate::format::format_count(total)
+        );
+        return Ok(InstallationResult::NotInstalled);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(matches) = matches.subcommand_matches("deps") {
+        return print_dependency_tree(matches).await;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(matches) = matches.subcommand_matches("validate-meta") {
+        return validate_meta(matches).await;
+    }
     if let Some(matches) = matches.subcommand_matches("loader-versions") {
         let generation = matches.get_one::<u32>("gen").copied();
         if let Some(g) = generation {
@@ -204,9 +515,16 @@ async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError
         let versions = crate::net::meta::fetch_loader_versions(&generation).await?;
         let loader_type = get_loader_type(matches)?;
         let betas = matches.get_flag("show-betas");
+        let min_build = matches.get_one::<i32>("min-build").copied();
+        let versions = versions
+            .get(&loader_type)
+            .unwrap()
+            .iter()
+            .filter(|version| min_build.is_none_or(|min| version.build() >= min))
+            .collect::<Vec<_>>();
 
         let mut out = String::new();
-        for version in versions.get(&loader_type).unwrap() {
+        for version in &versions {
             if betas || version.is_stable() {
                 out += &(version.version.clone() + " ");
             }
@@ -215,8 +533,7 @@ async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError
             "Latest {} Loader version: {}",
             loader_type.get_localized_name(),
             versions
-                .get(&loader_type)
-                .and_then(|list| list.first())
+                .first()
                 .map(|v| v.version.clone())
                 .unwrap_or("<not available>".to_owned())
         );
@@ -244,8 +561,9 @@ async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError
         let mut out = String::new();
         let snapshots = matches.get_flag("show-snapshots");
         let historical = matches.get_flag("show-historical");
+        let dates = matches.get_flag("show-dates");
         let info = get_minecraft_information(matches).await?;
-        for version in info.available_minecraft_versions {
+        for version in &info.available_minecraft_versions {
             let mut displayed = if snapshots && historical {
                 true
             } else {
@@ -258,18 +576,31 @@ async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError
                 displayed |= version.is_historical();
             }
             if displayed {
-                out += &(version.id.clone() + " ");
+                out += &version.id;
+                if dates {
+                    out += &format!(" [{}]", crate::format::format_date(&version.release_time));
+                }
+                match installable_sides(&info.intermediary_versions, &version.id) {
+                    (true, false) => out += " (client-only)",
+                    (false, true) => out += " (server-only)",
+                    _ => {}
+                }
+                out += " ";
             }
         }
+        let legend =
+            "Versions marked client-only or server-only can only be installed for that side.";
         #[cfg(not(target_arch = "wasm32"))]
         {
             println!("Available Minecraft versions:\n");
             println!("{}", out);
+            println!("\n{}", legend);
         }
         #[cfg(target_arch = "wasm32")]
         {
             log::info!("Available Minecraft versions:\n");
             log::info!("{}", out);
+            log::info!("{}", legend);
         }
         return Ok(InstallationResult::NotInstalled);
     }
@@ -308,7 +639,26 @@ async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError
 
     #[cfg(not(target_arch = "wasm32"))]
     {
+        let json_progress = matches
+            .get_one::<String>("progress")
+            .is_some_and(|mode| mode == "json");
+        if json_progress {
+            // Log lines also go to stderr and would break up the JSON stream
+            log::set_max_level(log::LevelFilter::Off);
+        }
         let fut = tokio::spawn(do_install(send, matches));
+        if json_progress {
+            // The channel closes once the installation and everything it spawned are done
+            while let Some((prog, msg)) = recv.recv().await {
+                print_progress_json("progress", prog, &msg);
+            }
+            let result = fut.await.unwrap();
+            match &result {
+                Ok(_) => print_progress_json("done", 1.0, ""),
+                Err(e) => print_progress_json("failed", 1.0, &e.0),
+            }
+            return result;
+        }
         let pb = ProgressBar::new(100).with_style(
             ProgressStyle::with_template("[{wide_bar:.green/cyan}] [{percent}%] ")
                 .unwrap()
@@ -330,6 +680,18 @@ async fn parse(matches: ArgMatches) -> Result<InstallationResult, InstallerError
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn print_progress_json(phase: &str, fraction: f32, message: &str) {
+    eprintln!(
+        "{}",
+        serde_json::json!({
+            "phase": phase,
+            "fraction": fraction,
+            "message": message,
+        })
+    );
+}
+
 #[cfg(target_arch = "wasm32")]
 fn add_status_note(note: &str) {
     log::info!("{}", note);
@@ -339,7 +701,7 @@ fn add_status_note(note: &str) {
     let _ = status_notes.insert_adjacent_html("beforeend", &format!("{}<br>", note));
 }
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "server"))]
 fn print_note_server_extraction() {
     add_status_note("Make sure to fully extract the zip bundle before starting your server!");
 }
@@ -351,6 +713,24 @@ fn print_note_intermediary_generation(generation: u32) {
     println!("Using Intermediary Generation: {generation}");
 }
 
+fn print_note_latest_substitution(latest: &str, substitute: &str) {
+    let note = format!(
+        "The latest Minecraft version ({latest}) does not have Intermediary mappings yet, using {substitute} instead."
+    );
+    #[cfg(target_arch = "wasm32")]
+    add_status_note(&note);
+    #[cfg(not(target_arch = "wasm32"))]
+    println!("{note}");
+}
+
+fn print_note_corrected_version(input: &str, id: &str) {
+    let note = format!("Minecraft version {input} is spelled {id}, using {id}.");
+    #[cfg(target_arch = "wasm32")]
+    add_status_note(&note);
+    #[cfg(not(target_arch = "wasm32"))]
+    println!("{note}");
+}
+
 fn print_note_excluding_flap(_sender: &UnboundedSender<(f32, String)>) {
     #[cfg(target_arch = "wasm32")]
     add_status_note("Not installing Flap.");
@@ -358,13 +738,481 @@ fn print_note_excluding_flap(_sender: &UnboundedSender<(f32, String)>) {
     let _ = _sender.send((0.0, "Not installing Flap.".to_owned()));
 }
 
+fn open_install_location(
+    _sender: &UnboundedSender<(f32, String)>,
+    matches: &ArgMatches,
+    _location: &Path,
+) {
+    if !matches.get_flag("open") {
+        return;
+    }
+    #[cfg(target_arch = "wasm32")]
+    log::info!("Ignoring 'open' option as it is of no value!");
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = super::open_folder(_location) {
+        let _ = _sender.send((1.0, e.0));
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn verify_client(matches: &ArgMatches) -> Result<InstallationResult, InstallerError> {
+    let (profile, problems) = crate::actions::client::verify_profile(
+        matches.get_one::<PathBuf>("dir").unwrap(),
+        matches.get_one::<String>("minecraft-version").unwrap(),
+        get_loader_type(matches)?,
+        matches.get_one::<u32>("gen").copied(),
+    )?;
+    if problems.is_empty() {
+        println!("{profile} looks intact.");
+        return Ok(InstallationResult::NotInstalled);
+    }
+    println!("Found problems with {profile}:");
+    for problem in &problems {
+        println!("  {problem}");
+    }
+    Err(InstallerError(format!(
+        "{profile} is broken; reinstall it to fix {} problem(s)",
+        crate::format::format_count(problems.len() as u64)
+    )))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn print_dependency_tree(matches: &ArgMatches) -> Result<InstallationResult, InstallerError> {
+    let side = match matches
+        .get_one::<String>("side")
+        .unwrap()
+        .to_lowercase()
+        .as_str()
+    {
+        "server" => GameSide::Server,
+        _ => GameSide::Client,
+    };
+    let (version, intermediary, info) = get_minecraft_version(
+        matches,
+        matches.get_one::<String>("minecraft-version").unwrap(),
+        side.clone(),
+    )
+    .await?;
+    let loader_type = get_loader_type(matches)?;
+    let all_loader_versions =
+        crate::net::meta::fetch_loader_versions(&info.calamus_generation).await?;
+    let loader_version =
+        get_loader_version(matches, all_loader_versions.get(&loader_type).unwrap())?;
+    let (profile_name, launch_json) = crate::net::meta::fetch_launch_json(
+        side.clone(),
+        &intermediary,
+        &loader_type,
+        &loader_version,
+        &info.calamus_generation,
+    )
+    .await?;
+    let upgrades =
+        crate::net::meta::fetch_profile_libraries(&info.calamus_generation, &intermediary.version)
+            .await?;
+
+    let mut groups =
+        crate::net::meta::group_launch_libraries(&launch_json, &loader_type, &upgrades)
+            .into_iter()
+            .map(|(group, libraries)| (group.to_owned(), libraries))
+            .collect::<Vec<_>>();
+    let flap = crate::net::maven::get_flap_version().await?;
+    groups.insert(
+        2.min(groups.len()),
+        (
+            "flap".to_owned(),
+            vec![(
+                format!("net.ornithemc:flap:{}", flap.version),
+                crate::net::maven::MAVEN_URL.to_owned(),
+            )],
+        ),
+    );
+    if let GameSide::Client = side {
+        let (_, lwjgl_version) = crate::net::manifest::find_lwjgl_url_version(&version).await?;
+        groups.insert(
+            3.min(groups.len()),
+            (
+                "lwjgl".to_owned(),
+                vec![(
+                    format!("org.lwjgl:lwjgl:{lwjgl_version}"),
+                    format!("Minecraft {} launch json", version.id),
+                )],
+            ),
+        );
+    }
+
+    println!("{profile_name} ({})", side.id());
+    for (index, (group, libraries)) in groups.iter().enumerate() {
+        let last_group = index + 1 == groups.len();
+        let (branch, indent) = if last_group {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        println!("{branch}{group}");
+        for (index, (name, source)) in libraries.iter().enumerate() {
+            let branch = if index + 1 == libraries.len() {
+                "└── "
+            } else {
+                "├── "
+            };
+            println!("{indent}{branch}{name} ({source})");
+        }
+    }
+    Ok(InstallationResult::NotInstalled)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn report_check<T>(
+    name: &str,
+    result: Result<T, InstallerError>,
+    failures: &mut usize,
+) -> Option<T> {
+    match result {
+        Ok(value) => {
+            println!("ok      {name}");
+            Some(value)
+        }
+        Err(e) => {
+            println!("FAILED  {name}: {}", e.0);
+            *failures += 1;
+            None
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn check_launch_json(json: &serde_json::Value) -> Result<(), InstallerError> {
+    let problems = crate::net::meta::launch_json_problems(json);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(InstallerError(problems.join(", ")))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn validate_meta(matches: &ArgMatches) -> Result<InstallationResult, InstallerError> {
+    use crate::net::{manifest, maven, meta};
+
+    let side = match matches
+        .get_one::<String>("side")
+        .unwrap()
+        .to_lowercase()
+        .as_str()
+    {
+        "server" => GameSide::Server,
+        _ => GameSide::Client,
+    };
+    let generation = matches.get_one::<u32>("gen").copied();
+    let loader_type = get_loader_type(matches)?;
+    let mut failures = 0;
+
+    report_check(
+        "intermediary generations",
+        meta::fetch_intermediary_generations().await,
+        &mut failures,
+    );
+    let info = report_check(
+        "version manifest and intermediary versions",
+        get_minecraft_information(matches).await,
+        &mut failures,
+    );
+    let loader_versions = report_check(
+        "loader versions",
+        meta::fetch_loader_versions(&generation).await,
+        &mut failures,
+    );
+    let selected = info.as_ref().and_then(|info| {
+        report_check(
+            "minecraft version",
+            find_minecraft_version(
+                info,
+                matches.get_one::<String>("minecraft-version").unwrap(),
+                &side,
+            ),
+            &mut failures,
+        )
+    });
+    if let Some((version, intermediary)) = selected {
+        report_check(
+            "version details",
+            version.get_jar_download_url(&side).await,
+            &mut failures,
+        );
+        let vanilla = manifest::fetch_launch_json(&version, &generation)
+            .await
+            .and_then(|(_, json)| Ok(serde_json::from_str::<serde_json::Value>(&json)?));
+        report_check(
+            "vanilla launch json",
+            vanilla.and_then(|json| check_launch_json(&json)),
+            &mut failures,
+        );
+        report_check(
+            "library upgrades",
+            meta::fetch_profile_libraries(&generation, &intermediary.version).await,
+            &mut failures,
+        );
+        let loader_version = loader_versions.as_ref().and_then(|versions| {
+            report_check(
+                "loader version",
+                get_loader_version(matches, versions.get(&loader_type).unwrap()),
+                &mut failures,
+            )
+        });
+        if let Some(loader_version) = loader_version {
+            let launch_json = meta::fetch_launch_json(
+                side,
+                &intermediary,
+                &loader_type,
+                &loader_version,
+                &generation,
+            )
+            .await;
+            report_check(
+                "loader launch json",
+                launch_json.and_then(|(_, json)| check_launch_json(&json)),
+                &mut failures,
+            );
+        }
+    }
+    report_check(
+        "flap version",
+        maven::get_flap_version().await,
+        &mut failures,
+    );
+
+    if failures > 0 {
+        return Err(InstallerError(format!(
+            "{failures} metadata check(s) failed"
+        )));
+    }
+    println!("All metadata responses are valid.");
+    Ok(InstallationResult::NotInstalled)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn uninstall_client(
+    send: UnboundedSender<(f32, String)>,
+    matches: &ArgMatches,
+) -> Result<InstallationResult, InstallerError> {
+    use std::io::IsTerminal;
+
+    let plan = crate::actions::client::plan_uninstall(
+        matches.get_one::<PathBuf>("dir").unwrap().clone(),
+        matches.get_one::<String>("minecraft-version").unwrap(),
+        get_loader_type(matches)?,
+        matches.get_one::<u32>("gen").copied(),
+    )?;
+    println!("The following will be removed:");
+    for dir in &plan.directories {
+        println!("  directory {}", dir.display());
+    }
+    for profile in &plan.profiles {
+        println!("  launcher profile {profile}");
+    }
+    if matches.get_flag("dry-run") {
+        return Ok(InstallationResult::NotInstalled);
+    }
+    if !matches.get_flag("yes") && std::io::stdin().is_terminal() {
+        print!("Proceed? [y/N] ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            println!("Nothing was removed.");
+            return Ok(InstallationResult::NotInstalled);
+        }
+    }
+    crate::actions::client::uninstall(send, plan)?;
+    Ok(InstallationResult::NotInstalled)
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "server"))]
+async fn find_java(
+    send: &UnboundedSender<(f32, String)>,
+    version: &MinecraftVersion,
+) -> Result<PathBuf, InstallerError> {
+    let required = crate::net::manifest::required_java_version(version).await?;
+    let installations = crate::actions::java::find_installations();
+    for java in &installations {
+        let _ = send.send((
+            0.0,
+            format!(
+                "Found Java {} at {}",
+                java.major_version,
+                java.path.display()
+            ),
+        ));
+    }
+    let java = crate::actions::java::select_compatible(&installations, required)?;
+    let _ = send.send((
+        0.0,
+        format!(
+            "Using Java {} for Minecraft {} (requires Java {required})",
+            java.major_version, version.id
+        ),
+    ));
+    Ok(java.path.clone())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn install_clients(
+    send: UnboundedSender<(f32, String)>,
+    minecraft_versions: Vec<(MinecraftVersion, IntermediaryVersion)>,
+    parallel: usize,
+    loader_type: LoaderType,
+    loader_version: LoaderVersion,
+    generation: Option<u32>,
+    location: PathBuf,
+    create_profile: bool,
+    include_flap: bool,
+    portable: bool,
+    asset_concurrency: Option<usize>,
+    release_time_mtime: bool,
+) -> Result<(), InstallerError> {
+    let count = minecraft_versions.len();
+    let progress = std::sync::Arc::new(std::sync::Mutex::new(vec![0.0f32; count]));
+    let mut installs = tokio::task::JoinSet::new();
+    let mut forwarders = Vec::new();
+    for (index, (minecraft_version, intermediary)) in minecraft_versions.into_iter().enumerate() {
+        while installs.len() >= parallel {
+            join_client_install(&mut installs).await?;
+        }
+        let (sender, mut receiver) = unbounded_channel::<(f32, String)>();
+        let send = send.clone();
+        let progress = progress.clone();
+        let id = minecraft_version.id.clone();
+        forwarders.push(tokio::spawn(async move {
+            while let Some((prog, msg)) = receiver.recv().await {
+                let total = match progress.lock() {
+                    Ok(mut progress) => {
+                        progress[index] = prog.clamp(0.0, 1.0);
+                        progress.iter().sum::<f32>() / count as f32
+                    }
+                    Err(_) => prog,
+                };
+                let msg = if msg.is_empty() {
+                    msg
+                } else {
+                    format!("[{id}] {msg}")
+                };
+                let _ = send.send((total, msg));
+            }
+        }));
+        installs.spawn(crate::actions::client::install(
+            sender,
+            minecraft_version,
+            intermediary,
+            loader_type.clone(),
+            loader_version.clone(),
+            generation,
+            location.clone(),
+            create_profile,
+            include_flap,
+            portable,
+            asset_concurrency,
+            release_time_mtime,
+        ));
+    }
+    while !installs.is_empty() {
+        join_client_install(&mut installs).await?;
+    }
+    for forwarder in forwarders {
+        let _ = forwarder.await;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn join_client_install(
+    installs: &mut tokio::task::JoinSet<Result<InstallResult, InstallerError>>,
+) -> Result<(), InstallerError> {
+    match installs.join_next().await {
+        Some(Ok(result)) => {
+            log_install_result(&result?);
+            Ok(())
+        }
+        Some(Err(e)) => Err(InstallerError(e.to_string())),
+        None => Ok(()),
+    }
+}
+
+async fn pin_flap_version(matches: &ArgMatches) -> Result<(), InstallerError> {
+    match matches.get_one::<String>("flap-version") {
+        Some(version) => crate::net::maven::pin_flap_version(version).await,
+        None => Ok(()),
+    }
+}
+
+fn log_install_result(result: &InstallResult) {
+    for dir in &result.created {
+        log::debug!("Created {}", dir.display());
+    }
+    for dir in &result.replaced {
+        log::debug!("Replaced {}", dir.display());
+    }
+    match &result.profile {
+        Some(ProfileChange::Added(name)) => log::debug!("Added launcher profile {name}"),
+        Some(ProfileChange::Updated(name)) => log::debug!("Updated launcher profile {name}"),
+        None => {}
+    }
+}
+
 async fn do_install(
     send: UnboundedSender<(f32, String)>,
     matches: ArgMatches,
 ) -> Result<InstallationResult, InstallerError> {
     if let Some(matches) = matches.subcommand_matches("client") {
-        let (minecraft_version, intermediary, info) =
-            get_minecraft_version(matches, GameSide::Client).await?;
+        crate::actions::client::set_repair_profiles(matches.get_flag("repair-profiles"));
+        crate::actions::client::set_select_profile(matches.get_flag("set-default"));
+        if let Some(repair) = matches.subcommand_matches("repair-profile") {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                crate::actions::client::repair_profile(
+                    send,
+                    repair.get_one::<PathBuf>("dir").unwrap().clone(),
+                    repair.get_one::<String>("minecraft-version").unwrap(),
+                    get_loader_type(repair)?,
+                    repair.get_one::<u32>("gen").copied(),
+                )?;
+                return Ok(InstallationResult::NotInstalled);
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                let _ = repair;
+                return Err(InstallerError(
+                    "Repairing profiles is not available on the web".to_owned(),
+                ));
+            }
+        }
+        if let Some(verify) = matches.subcommand_matches("verify") {
+            #[cfg(not(target_arch = "wasm32"))]
+            return verify_client(verify);
+            #[cfg(target_arch = "wasm32")]
+            {
+                let _ = verify;
+                return Err(InstallerError(
+                    "Verifying installations is not available on the web".to_owned(),
+                ));
+            }
+        }
+        if let Some(uninstall) = matches.subcommand_matches("uninstall") {
+            #[cfg(not(target_arch = "wasm32"))]
+            return uninstall_client(send, uninstall);
+            #[cfg(target_arch = "wasm32")]
+            {
+                let _ = uninstall;
+                return Err(InstallerError(
+                    "Uninstalling is not available on the web".to_owned(),
+                ));
+            }
+        }
+        check_mappings(matches)?;
+        let info = load_minecraft_information(&send, matches).await?;
+        let minecraft_versions = matches
+            .get_many::<String>("minecraft-version")
+            .unwrap()
+            .map(|id| find_minecraft_version(&info, id, &GameSide::Client))
+            .collect::<Result<Vec<_>, _>>()?;
         let loader_type = get_loader_type(matches)?;
         let all_loader_versions =
             crate::net::meta::fetch_loader_versions(&info.calamus_generation).await?;
@@ -377,42 +1225,136 @@ async fn do_install(
             let _ = send.send((0.0, "Not generating profile entry.".to_owned()));
         }
         let exclude_flap = matches.get_flag("exclude-flap");
+        pin_flap_version(matches).await?;
         if exclude_flap {
             print_note_excluding_flap(&send);
         }
-        crate::actions::client::install(
-            send,
-            minecraft_version,
-            intermediary,
-            loader_type,
-            loader_version,
-            info.calamus_generation,
-            location,
-            create_profile,
-            !exclude_flap,
-        )
-        .await?;
+        let portable = matches.get_flag("portable");
+        #[cfg(not(target_arch = "wasm32"))]
+        if matches.get_flag("create-dir") {
+            crate::actions::client::create_game_dir(&send, &location, create_profile)?;
+        }
+        let release_time_mtime = matches.get_flag("release-time-mtime");
+        // The mirrors may come from the environment, so only look at them when downloading assets
+        #[cfg(not(target_arch = "wasm32"))]
+        if matches.get_flag("download-assets") {
+            if let Some(url) = matches.get_one::<String>("assets-url") {
+                crate::actions::assets::set_resources_url(url)?;
+            }
+            if let Some(url) = matches.get_one::<String>("asset-index-url") {
+                crate::actions::assets::set_index_url(url)?;
+            }
+        }
+        let asset_concurrency = matches
+            .get_flag("download-assets")
+            .then(|| usize::from(*matches.get_one::<u16>("asset-concurrency").unwrap()));
+        #[cfg(not(target_arch = "wasm32"))]
+        if minecraft_versions.len() > 1 {
+            install_clients(
+                send.clone(),
+                minecraft_versions,
+                usize::from(*matches.get_one::<u16>("parallel").unwrap()),
+                loader_type,
+                loader_version,
+                info.calamus_generation,
+                location.clone(),
+                create_profile,
+                !exclude_flap,
+                portable,
+                asset_concurrency,
+                release_time_mtime,
+            )
+            .await?;
+            open_install_location(&send, matches, &location);
+            return Ok(InstallationResult::Installed);
+        }
+        for (minecraft_version, intermediary) in minecraft_versions {
+            let result = crate::actions::client::install(
+                send.clone(),
+                minecraft_version,
+                intermediary,
+                loader_type.clone(),
+                loader_version.clone(),
+                info.calamus_generation,
+                location.clone(),
+                create_profile,
+                !exclude_flap,
+                portable,
+                asset_concurrency,
+                release_time_mtime,
+            )
+            .await?;
+            log_install_result(&result);
+        }
+        open_install_location(&send, matches, &location);
         return Ok(InstallationResult::Installed);
     }
 
+    #[cfg(feature = "server")]
     if let Some(matches) = matches.subcommand_matches("server") {
+        check_mappings(matches)?;
         #[cfg(target_arch = "wasm32")]
         print_note_server_extraction();
-        let (minecraft_version, intermediary, info) =
-            get_minecraft_version(matches, GameSide::Server).await?;
-
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(jar) = matches.get_one::<PathBuf>("server-launcher-jar") {
+            crate::actions::server::set_launcher_jar(jar)?;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(class) = matches.get_one::<String>("main-class") {
+            crate::actions::server::set_main_class(class)?;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::actions::server::set_sequential(matches.get_flag("sequential"));
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(seconds) = matches.get_one::<u64>("timeout-per-file") {
+            crate::net::set_file_timeout(std::time::Duration::from_secs(*seconds));
+        }
         let loader_type = get_loader_type(matches)?;
+        let location = matches.get_one::<PathBuf>("dir").unwrap().clone();
+        let minecraft_version_arg = match matches.get_one::<String>("minecraft-version") {
+            Some(version) => version.clone(),
+            // Only `server run` may leave out the version, running whatever is installed
+            None => {
+                let installed = crate::actions::server::installed_version(&location, &loader_type)
+                    .ok_or_else(|| {
+                        InstallerError(format!(
+                            "No Minecraft version given and no server installation found in {}",
+                            location.display()
+                        ))
+                    })?;
+                let _ = send.send((
+                    0.0,
+                    format!("Using the installed Minecraft version {installed}"),
+                ));
+                installed
+            }
+        };
+        let info = load_minecraft_information(&send, matches).await?;
+        let (minecraft_version, intermediary) =
+            find_minecraft_version(&info, &minecraft_version_arg, &GameSide::Server)?;
+
         let all_loader_versions =
             crate::net::meta::fetch_loader_versions(&info.calamus_generation).await?;
         let loader_versions = all_loader_versions.get(&loader_type).unwrap();
         let loader_version = get_loader_version(matches, loader_versions)?;
-        let location = matches.get_one::<PathBuf>("dir").unwrap().clone();
         let exclude_flap = matches.get_flag("exclude-flap");
+        pin_flap_version(matches).await?;
         if exclude_flap {
             print_note_excluding_flap(&send);
         }
         if let Some(matches) = matches.subcommand_matches("run") {
-            let java = matches.get_one::<PathBuf>("java");
+            let java = match matches.get_one::<String>("java") {
+                #[cfg(not(target_arch = "wasm32"))]
+                Some(java) if java.eq_ignore_ascii_case("auto") => {
+                    Some(find_java(&send, &minecraft_version).await?)
+                }
+                Some(java) => Some(PathBuf::from(java)),
+                None => None,
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            if matches.get_flag("accept-eula") {
+                crate::actions::server::accept_eula(&location)?;
+            }
             let run_args = matches.get_one::<String>("args");
             let installed = crate::actions::server::install_and_run(
                 send,
@@ -423,8 +1365,13 @@ async fn do_install(
                 info.calamus_generation,
                 location,
                 !exclude_flap,
-                java,
+                java.as_ref(),
                 run_args.map(|s| s.split(" ")),
+                matches
+                    .get_one::<String>("post-args")
+                    .map(|s| s.split_whitespace().map(str::to_owned).collect())
+                    .unwrap_or_default(),
+                matches.get_flag("gui"),
             )
             .await?;
             return Ok(match installed {
@@ -433,23 +1380,35 @@ async fn do_install(
             });
         }
         crate::actions::server::install(
-            send,
+            send.clone(),
             minecraft_version,
             intermediary,
             loader_type,
             loader_version,
             info.calamus_generation,
-            location,
+            location.clone(),
             *matches.get_one::<bool>("download-minecraft").unwrap(),
             !exclude_flap,
         )
         .await?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if matches.get_flag("pack-libraries") {
+            crate::actions::server::pack_libraries(&send, &location)?;
+        }
+        open_install_location(&send, matches, &location);
         return Ok(InstallationResult::Installed);
     }
 
+    #[cfg(feature = "mmc")]
     if let Some(matches) = matches.subcommand_matches("prism") {
-        let (minecraft_version, intermediary, info) =
-            get_minecraft_version(matches, GameSide::Client).await?;
+        check_mappings(matches)?;
+        // Several instances may be generated in one go, sharing the version metadata
+        let info = load_minecraft_information(&send, matches).await?;
+        let minecraft_versions = matches
+            .get_many::<String>("minecraft-version")
+            .unwrap()
+            .map(|id| find_minecraft_version(&info, id, &GameSide::Client))
+            .collect::<Result<Vec<_>, _>>()?;
         let loader_type = get_loader_type(matches)?;
         let all_loader_versions =
             crate::net::meta::fetch_loader_versions(&info.calamus_generation).await?;
@@ -462,23 +1421,102 @@ async fn do_install(
             log::info!("Ignoring 'copy-profile-path' option as it is of no value!")
         }
         let generate_zip = *matches.get_one::<bool>("generate-zip").unwrap();
+        let shared_components = matches.get_flag("shared-components");
+        if shared_components && !generate_zip {
+            return Err(InstallerError(
+                "--shared-components always writes a zip and can't be combined with --generate-zip false"
+                    .to_owned(),
+            ));
+        }
+        let on_exists = get_on_exists(matches)?;
+        let managed_pack = matches
+            .get_one::<String>("managed-pack")
+            .map(|id| ManagedPack {
+                id: id.clone(),
+                pack_type: matches
+                    .get_one::<String>("managed-pack-type")
+                    .unwrap()
+                    .to_lowercase(),
+            });
+        let window = WindowSettings {
+            width: matches.get_one::<u32>("window-width").copied(),
+            height: matches.get_one::<u32>("window-height").copied(),
+            fullscreen: matches.get_flag("fullscreen"),
+        };
         let exclude_flap = matches.get_flag("exclude-flap");
+        pin_flap_version(matches).await?;
         if exclude_flap {
             print_note_excluding_flap(&send);
         }
-        crate::actions::prism_pack::install(
-            send,
-            minecraft_version,
-            intermediary,
-            loader_type,
-            loader_version,
-            output_dir,
-            copy_profile_path,
-            generate_zip,
-            info.calamus_generation,
-            !exclude_flap,
-        )
-        .await?;
+        let count = minecraft_versions.len();
+        if shared_components {
+            let mut instances = Vec::new();
+            for (minecraft_version, intermediary) in minecraft_versions {
+                let _ = send.send((
+                    0.0,
+                    format!(
+                        "Generating instance {}/{} ({})",
+                        instances.len() + 1,
+                        count,
+                        minecraft_version.id
+                    ),
+                ));
+                instances.push(
+                    crate::actions::prism_pack::generate(
+                        &send,
+                        &minecraft_version,
+                        &intermediary,
+                        &loader_type,
+                        &loader_version,
+                        &info.calamus_generation,
+                        !exclude_flap,
+                        managed_pack.clone(),
+                        window,
+                    )
+                    .await?,
+                );
+            }
+            crate::actions::prism_pack::write_shared(
+                &send,
+                &instances,
+                &loader_type,
+                &output_dir,
+                copy_profile_path,
+            )?;
+            open_install_location(&send, matches, &output_dir);
+            return Ok(InstallationResult::Installed);
+        }
+        for (index, (minecraft_version, intermediary)) in minecraft_versions.into_iter().enumerate()
+        {
+            if count > 1 {
+                let _ = send.send((
+                    0.0,
+                    format!(
+                        "Generating instance {}/{} ({})",
+                        index + 1,
+                        count,
+                        minecraft_version.id
+                    ),
+                ));
+            }
+            crate::actions::prism_pack::install(
+                send.clone(),
+                minecraft_version,
+                intermediary,
+                loader_type.clone(),
+                loader_version.clone(),
+                output_dir.clone(),
+                copy_profile_path,
+                generate_zip,
+                info.calamus_generation,
+                !exclude_flap,
+                on_exists,
+                managed_pack.clone(),
+                window,
+            )
+            .await?;
+        }
+        open_install_location(&send, matches, &output_dir);
         return Ok(InstallationResult::Installed);
     }
 
@@ -509,22 +1547,101 @@ async fn get_minecraft_information(
         intermediary_versions,
         available_minecraft_versions,
         calamus_generation: generation,
+        latest: minecraft_versions.latest,
     })
 }
 
+async fn load_minecraft_information(
+    send: &UnboundedSender<(f32, String)>,
+    matches: &ArgMatches,
+) -> Result<MinecraftInformation, InstallerError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return crate::net::with_status(send.clone(), get_minecraft_information(matches)).await;
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = send;
+        get_minecraft_information(matches).await
+    }
+}
+
+/// The latest release may not have Intermediary mappings yet, in which case the newest
+/// release that does is used.
+fn resolve_latest_version(
+    info: &MinecraftInformation,
+    side: &GameSide,
+) -> Result<String, InstallerError> {
+    let installable = |id: &str| {
+        let (client, server) = installable_sides(&info.intermediary_versions, id);
+        match side {
+            GameSide::Client => client,
+            GameSide::Server => server,
+        }
+    };
+    let latest = info.latest.release();
+    if installable(latest) {
+        return Ok(latest.to_owned());
+    }
+    let fallback = info
+        .available_minecraft_versions
+        .iter()
+        .find(|version| version.is_release() && installable(&version.id))
+        .ok_or(InstallerError(
+            "Could not find any installable Minecraft release!".to_owned(),
+        ))?;
+    print_note_latest_substitution(latest, &fallback.id);
+    Ok(fallback.id.clone())
+}
+
+fn installable_sides(
+    intermediary_versions: &HashMap<String, IntermediaryVersion>,
+    version: &str,
+) -> (bool, bool) {
+    if intermediary_versions.contains_key(version) {
+        return (true, true);
+    }
+    (
+        intermediary_versions.contains_key(&(version.to_owned() + "-client")),
+        intermediary_versions.contains_key(&(version.to_owned() + "-server")),
+    )
+}
+
 struct MinecraftInformation {
     intermediary_versions: HashMap<String, IntermediaryVersion>,
     available_minecraft_versions: Vec<MinecraftVersion>,
     calamus_generation: Option<u32>,
+    latest: LatestVersions,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 async fn get_minecraft_version(
     matches: &ArgMatches,
+    minecraft_version_arg: &str,
     side: GameSide,
 ) -> Result<(MinecraftVersion, IntermediaryVersion, MinecraftInformation), InstallerError> {
     let info = get_minecraft_information(matches).await?;
-    let minecraft_version_arg = matches.get_one::<String>("minecraft-version").unwrap();
+    let (version, intermediary) = find_minecraft_version(&info, minecraft_version_arg, &side)?;
+    Ok((version, intermediary, info))
+}
 
+fn find_minecraft_version(
+    info: &MinecraftInformation,
+    minecraft_version_arg: &str,
+    side: &GameSide,
+) -> Result<(MinecraftVersion, IntermediaryVersion), InstallerError> {
+    if minecraft_version_arg.eq_ignore_ascii_case("latest") {
+        let latest = resolve_latest_version(info, side)?;
+        return find_minecraft_version(info, &latest, side);
+    }
+    // Ids are case-sensitive, but e.g. `B1.7.3` can only have meant `b1.7.3`
+    let versions = &info.available_minecraft_versions;
+    if !versions.iter().any(|v| v.id == minecraft_version_arg)
+        && let Some(version) = versions
+            .iter()
+            .find(|v| v.id.eq_ignore_ascii_case(minecraft_version_arg))
+    {
+        print_note_corrected_version(minecraft_version_arg, &version.id);
+        return find_minecraft_version(info, &version.id, side);
+    }
     let intermediary_versions = &info.intermediary_versions;
     for version in &info.available_minecraft_versions {
         if version.id == *minecraft_version_arg {
@@ -532,7 +1649,7 @@ async fn get_minecraft_version(
                 .get(&version.id)
                 .or_else(|| intermediary_versions.get(&(version.id.to_owned() + "-" + side.id())));
             if let Some(int) = intermediary {
-                return Ok((version.clone(), int.clone(), info));
+                return Ok((version.clone(), int.clone()));
             } else if !intermediary_versions.contains_key(&version.id)
                 && intermediary_versions
                     .contains_key(&(version.id.to_owned() + "-" + side.other_side().id()))
@@ -552,7 +1669,13 @@ async fn get_minecraft_version(
     Err(InstallerError(
         "Could not find Minecraft version ".to_owned()
             + minecraft_version_arg
-            + " among supported versions!",
+            + " among supported versions!"
+            + &did_you_mean(
+                minecraft_version_arg,
+                info.available_minecraft_versions
+                    .iter()
+                    .map(|v| v.id.as_str()),
+            ),
     ))
 }
 
@@ -568,32 +1691,135 @@ fn get_loader_type(matches: &ArgMatches) -> Result<LoaderType, InstallerError> {
     )
 }
 
+/// Ornithe always installs with Calamus intermediary, so any other choice is
+/// rejected with an explanation instead of being silently ignored.
+fn check_mappings(matches: &ArgMatches) -> Result<(), InstallerError> {
+    let Some(mappings) = matches.get_one::<String>("mappings") else {
+        return Ok(());
+    };
+    match mappings.to_lowercase().as_str() {
+        "calamus" | "intermediary" | "calamus-intermediary" => Ok(()),
+        _ => Err(InstallerError(format!(
+            "Unsupported mappings '{mappings}'. Ornithe uses Calamus intermediary mappings at runtime; \
+            named mappings such as Feather or Mojmap are applied by mod developers at development time \
+            and cannot be chosen when installing."
+        ))),
+    }
+}
+
+#[cfg(feature = "mmc")]
+fn get_on_exists(matches: &ArgMatches) -> Result<OnExists, InstallerError> {
+    Ok(
+        match matches
+            .get_one::<String>("on-exists")
+            .unwrap()
+            .to_lowercase()
+            .as_str()
+        {
+            "abort" => OnExists::Abort,
+            "overwrite" => OnExists::Overwrite,
+            "backup" => OnExists::Backup,
+            &_ => {
+                return Err(InstallerError("Unsupported on-exists action!".to_owned()));
+            }
+        },
+    )
+}
+
 fn get_loader_version(
     matches: &ArgMatches,
     versions: &Vec<LoaderVersion>,
 ) -> Result<LoaderVersion, InstallerError> {
     let arg = matches.get_one::<String>("loader-version").unwrap();
+    let min_build = matches.get_one::<i32>("min-build").copied();
+    let new_enough = |version: &LoaderVersion| min_build.is_none_or(|min| version.build() >= min);
 
     if *arg == "latest" {
-        return versions.first().cloned().ok_or(InstallerError(
-            "Failed to find loader version in list".to_owned(),
-        ));
+        return versions
+            .iter()
+            .find(|version| new_enough(version))
+            .cloned()
+            .ok_or(InstallerError(match min_build {
+                Some(min) => format!("No loader version has a build number of at least {min}"),
+                None => "Failed to find loader version in list".to_owned(),
+            }));
     }
 
     for version in versions {
         if version.version == *arg {
+            if !new_enough(version) {
+                return Err(InstallerError(format!(
+                    "Loader version {} is build {}, older than the requested --min-build",
+                    version.version,
+                    version.build()
+                )));
+            }
             return Ok(version.clone());
         }
     }
 
     Err(InstallerError(
-        "Could not find loader version: ".to_owned() + arg,
+        "Could not find loader version: ".to_owned()
+            + arg
+            + "."
+            + &did_you_mean(arg, versions.iter().map(|v| v.version.as_str()))
+            + " Use 'latest' to install the newest version.",
     ))
 }
 
+fn did_you_mean<'a>(input: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    let max_distance = (input.len() / 3).max(2);
+    let mut matches = candidates
+        .filter_map(|candidate| {
+            let distance = if candidate.starts_with(input) {
+                0
+            } else {
+                levenshtein(input, candidate)
+            };
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect::<Vec<_>>();
+    // stable sort keeps the (newest first) order among equally close candidates
+    matches.sort_by_key(|(distance, _)| *distance);
+    if matches.is_empty() {
+        return String::new();
+    }
+    let suggestions = matches
+        .iter()
+        .take(3)
+        .map(|(_, candidate)| *candidate)
+        .collect::<Vec<_>>();
+    format!(" Did you mean {}?", suggestions.join(", "))
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 fn add_arguments(command: Command) -> Command {
+    add_version_arguments(command)
+        .arg(arg!(--"exclude-flap" "Do not include Flap (Cross-Intermediary Remapper)"))
+        .arg(arg!(--"flap-version" <VERSION> "Flap version to include (default: the latest release)").conflicts_with("exclude-flap"))
+        .arg(arg!(--open "Open the installation directory after a successful installation"))
+        .arg(arg!(--mappings <MAPPINGS> "The intermediary mappings to install with (only calamus is supported)"))
+}
+
+fn add_version_arguments(command: Command) -> Command {
     add_gen_argument(command)
-        .arg(arg!(-m --"minecraft-version" <VERSION> "Minecraft version to use").required(true))
+        .arg(
+            arg!(-m --"minecraft-version" <VERSION> "Minecraft version to use, or 'latest'")
+                .required(true),
+        )
         .arg(
             arg!(--"loader-type" <TYPE> "Loader type to use")
                 .default_value("fabric")
@@ -601,7 +1827,64 @@ fn add_arguments(command: Command) -> Command {
                 .value_parser(["fabric", "quilt"]),
         )
         .arg(arg!(--"loader-version" <VERSION> "Loader version to use").default_value("latest"))
-        .arg(arg!(--"exclude-flap" "Do not include Flap (Cross-Intermediary Remapper)"))
+        .arg(min_build_argument())
+}
+
+fn add_installed_profile_arguments(command: Command) -> Command {
+    add_gen_argument(
+        command
+            .arg(
+                arg!(-d --dir <DIR> "Installation directory")
+                    .default_value(super::dot_minecraft_location())
+                    .value_parser(value_parser!(PathBuf)),
+            )
+            .arg(
+                arg!(-m --"minecraft-version" <VERSION> "Minecraft version of the installed profile")
+                    .required(true),
+            )
+            .arg(
+                arg!(--"loader-type" <TYPE> "Loader type of the installed profile")
+                    .default_value("fabric")
+                    .ignore_case(true)
+                    .value_parser(["fabric", "quilt"]),
+            ),
+    )
+}
+
+fn add_server_env_vars(command: Command) -> Command {
+    [
+        ("dir", "ORNITHE_DIR"),
+        ("minecraft-version", "ORNITHE_MC_VERSION"),
+        ("loader-type", "ORNITHE_LOADER_TYPE"),
+        ("loader-version", "ORNITHE_LOADER_VERSION"),
+        ("min-build", "ORNITHE_MIN_BUILD"),
+        ("gen", "ORNITHE_GEN"),
+        ("mappings", "ORNITHE_MAPPINGS"),
+        ("exclude-flap", "ORNITHE_EXCLUDE_FLAP"),
+        ("flap-version", "ORNITHE_FLAP_VERSION"),
+        ("download-minecraft", "ORNITHE_DOWNLOAD_MINECRAFT"),
+        ("server-launcher-jar", "ORNITHE_SERVER_LAUNCHER_JAR"),
+        ("main-class", "ORNITHE_MAIN_CLASS"),
+        ("pack-libraries", "ORNITHE_PACK_LIBRARIES"),
+        ("sequential", "ORNITHE_SEQUENTIAL"),
+        ("timeout-per-file", "ORNITHE_TIMEOUT_PER_FILE"),
+    ]
+    .into_iter()
+    .fold(command, |command, (arg, var)| {
+        command.mut_arg(arg, |arg| arg.env(var))
+    })
+    .mut_subcommand("run", |run| {
+        run.mut_arg("args", |arg| arg.env("ORNITHE_JAVA_ARGS"))
+            .mut_arg("post-args", |arg| arg.env("ORNITHE_SERVER_ARGS"))
+            .mut_arg("gui", |arg| arg.env("ORNITHE_SERVER_GUI"))
+            .mut_arg("java", |arg| arg.env("ORNITHE_JAVA"))
+            .mut_arg("accept-eula", |arg| arg.env("ORNITHE_ACCEPT_EULA"))
+    })
+}
+
+fn min_build_argument() -> clap::Arg {
+    arg!(--"min-build" <BUILD> "Only consider loader versions with at least this build number")
+        .value_parser(value_parser!(i32))
 }
 
 fn add_gen_argument(command: Command) -> Command {
@@ -611,3 +1894,36 @@ fn add_gen_argument(command: Command) -> Command {
             .alias("generation"),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("1.8.9", "1.8.9"), 0);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("1.8.8", "1.8.9"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn suggests_close_and_prefixed_versions() {
+        let versions = ["1.8.9", "1.12.2", "b1.7.3"];
+        assert_eq!(
+            did_you_mean("1.8.8", versions.into_iter()),
+            " Did you mean 1.8.9?"
+        );
+        assert_eq!(
+            did_you_mean("0.16", ["0.16.14", "0.15.11", "0.16.10"].into_iter()),
+            " Did you mean 0.16.14, 0.16.10?"
+        );
+    }
+
+    #[test]
+    fn suggests_nothing_when_no_version_is_close() {
+        // "1.8.8" is three edits away from both, one more than allowed for its length
+        assert_eq!(did_you_mean("1.8.8", ["1.12.2", "b1.7.3"].into_iter()), "");
+        assert_eq!(did_you_mean("13w16a", ["1.8.9"].into_iter()), "");
+    }
+}
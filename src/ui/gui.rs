@@ -29,10 +29,56 @@ use crate::{
 #[derive(PartialEq, Clone, Copy, Debug)]
 enum Mode {
     Client,
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
     Server,
+    #[cfg_attr(not(feature = "mmc"), allow(dead_code))]
     PrismLauncher,
 }
 
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum LoaderChannel {
+    Stable,
+    Beta,
+    All,
+}
+
+impl LoaderChannel {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn id(&self) -> &'static str {
+        match self {
+            LoaderChannel::Stable => "stable",
+            LoaderChannel::Beta => "beta",
+            LoaderChannel::All => "all",
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_id(id: &str) -> Option<Self> {
+        [
+            LoaderChannel::Stable,
+            LoaderChannel::Beta,
+            LoaderChannel::All,
+        ]
+        .into_iter()
+        .find(|channel| channel.id() == id)
+    }
+
+    fn includes(&self, version: &LoaderVersion) -> bool {
+        match self {
+            LoaderChannel::Stable => version.is_stable(),
+            LoaderChannel::Beta => version.is_beta(),
+            LoaderChannel::All => true,
+        }
+    }
+
+    fn default_version<'a>(&self, versions: &'a [LoaderVersion]) -> Option<&'a LoaderVersion> {
+        versions
+            .iter()
+            .find(|v| self.includes(v))
+            .or(versions.first())
+    }
+}
+
 pub async fn run() -> Result<(), InstallerError> {
     info!("Starting GUI installer...");
     if let Ok(locale) = current_locale::current_locale() {
@@ -54,13 +100,11 @@ pub async fn run() -> Result<(), InstallerError> {
 }
 
 async fn create_window() -> Result<(), InstallerError> {
-    let res = App::create().await;
-    if let Err(e) = res {
-        error!("{}", e.0);
-        display_dialog(t!("gui.error.generic"), &e.0);
+    #[cfg(not(target_arch = "wasm32"))]
+    if !wait_for_connection().await {
         return Ok(());
     }
-    let app = res.unwrap();
+    let app = App::create();
     #[cfg(not(target_arch = "wasm32"))]
     {
         let data = eframe::icon_data::from_png_bytes(crate::ORNITHE_ICON_BYTES)
@@ -136,6 +180,39 @@ async fn create_window() -> Result<(), InstallerError> {
     Ok(())
 }
 
+/// Reports a missing connection right away with the option to retry.
+/// Returns false if the user chose to quit.
+#[cfg(not(target_arch = "wasm32"))]
+async fn wait_for_connection() -> bool {
+    loop {
+        let Err(e) = net::meta::probe().await else {
+            return true;
+        };
+        error!("{}", e.0);
+        let retry: String = t!("gui.button.retry").into();
+        let proceed: String = t!("gui.button.continue_anyway").into();
+        let res = AsyncMessageDialog::new()
+            .set_title(t!("gui.error.no_connection"))
+            .set_level(rfd::MessageLevel::Warning)
+            .set_description(t!("gui.error.no_connection.message", error = e.0))
+            .set_buttons(MessageButtons::YesNoCancelCustom(
+                retry.clone(),
+                proceed.clone(),
+                t!("gui.button.quit").into(),
+            ))
+            .show()
+            .await;
+        // Depending on the platform custom buttons report either their label or their position
+        match res {
+            MessageDialogResult::Yes => continue,
+            MessageDialogResult::No => return true,
+            MessageDialogResult::Custom(label) if label == retry => continue,
+            MessageDialogResult::Custom(label) if label == proceed => return true,
+            _ => return false,
+        }
+    }
+}
+
 fn display_dialog<T: Into<String> + Display, M: Into<String> + Display>(title: T, message: M) {
     display_dialog_ext(title, message, MessageButtons::Ok, |_| {});
 }
@@ -177,7 +254,7 @@ struct App {
     selected_loader_type: LoaderType,
     selected_loader_version: String,
     available_loader_versions: HashMap<LoaderType, Vec<LoaderVersion>>,
-    show_betas: bool,
+    loader_channel: LoaderChannel,
     create_profile: bool,
     client_install_location: String,
     mmc_output_location: String,
@@ -195,8 +272,21 @@ struct App {
     #[cfg(not(target_arch = "wasm32"))]
     detonation_easter_egg: bool,
     include_flap: bool,
+    versions_loading: Option<Receiver<Result<LoadedVersions, InstallerError>>>,
+    versions_error: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    versions_status: (Option<UnboundedReceiver<(f32, String)>>, Option<String>),
     modals: Vec<ModalPopup>,
     modal_channel: (Sender<ModalPopup>, Receiver<ModalPopup>),
+    #[cfg(not(target_arch = "wasm32"))]
+    existing_install_channel: (
+        Sender<ExistingInstallChoice>,
+        Receiver<ExistingInstallChoice>,
+    ),
+    #[cfg(not(target_arch = "wasm32"))]
+    existing_install: Option<(String, Option<(String, u32)>)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    settings: super::settings::Settings,
     #[cfg(target_arch = "wasm32")]
     app_canvas: web_sys::HtmlCanvasElement,
     request_main_content_sizing_pass: bool,
@@ -206,6 +296,12 @@ struct App {
     small_viewport: bool,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+enum ExistingInstallChoice {
+    Reinstall,
+    UpdateProfile,
+}
+
 struct ModalPopup {
     title: String,
     message: String,
@@ -227,6 +323,20 @@ impl ModalPopup {
         }
     }
 
+    pub fn custom(
+        title: impl Into<String>,
+        message: impl Into<String>,
+        buttons: MessageButtons,
+        after: Box<dyn FnOnce(MessageDialogResult) + Send + 'static>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            buttons,
+            after,
+        }
+    }
+
     pub fn ok_ext(
         title: impl Into<String>,
         message: impl Into<String>,
@@ -337,68 +447,73 @@ struct FilePickResult {
     path: String,
 }
 
-impl App {
-    async fn create() -> Result<App, InstallerError> {
-        let mut available_minecraft_versions = Vec::new();
-        let mut available_intermediary_versions = Vec::new();
-        let mut available_loader_versions = HashMap::new();
-        let mut intermediary_versions = HashMap::new();
-        let manifest_future = net::manifest::fetch_versions(&None);
-        let intermediary_future = net::meta::fetch_intermediary_versions(&None);
-        let loader_future = net::meta::fetch_loader_versions(&None);
-
-        info!("Loading versions...");
-        match manifest_future.await {
-            Ok(versions) => {
-                for ele in versions.versions {
-                    available_minecraft_versions.push(ele);
-                }
-            }
-            _ => display_dialog(
-                t!("gui.error.loading"),
-                t!("gui.error.loading.minecraft_versions"),
-            ),
-        }
+struct LoadedVersions {
+    minecraft: Vec<MinecraftVersion>,
+    intermediary: HashMap<String, IntermediaryVersion>,
+    intermediary_ids: Vec<String>,
+    loaders: HashMap<LoaderType, Vec<LoaderVersion>>,
+}
 
-        match intermediary_future.await {
-            Ok(versions) => {
-                for v in versions {
-                    available_intermediary_versions.push(v.0.clone());
-                    intermediary_versions.insert(v.0, v.1);
-                }
+async fn load_versions() -> Result<LoadedVersions, InstallerError> {
+    let mut intermediary_ids = Vec::new();
+    let mut intermediary = HashMap::new();
+    let mut loaders = HashMap::new();
+    let manifest_future = net::manifest::fetch_versions(&None);
+    let intermediary_future = net::meta::fetch_intermediary_versions(&None);
+    let loader_future = net::meta::fetch_loader_versions(&None);
+
+    info!("Loading versions...");
+    let minecraft = manifest_future
+        .await
+        .map_err(|e| {
+            InstallerError(format!(
+                "{}\n\n{}",
+                t!("gui.error.loading.minecraft_versions"),
+                e.0
+            ))
+        })?
+        .versions;
+
+    match intermediary_future.await {
+        Ok(versions) => {
+            for v in versions {
+                intermediary_ids.push(v.0.clone());
+                intermediary.insert(v.0, v.1);
             }
-            _ => display_dialog(
-                t!("gui.error.loading"),
-                t!("gui.error.loading.intermediary_versions"),
-            ),
         }
-        if available_minecraft_versions.is_empty() {
-            return Err(InstallerError::from(t!(
-                "gui.error.no_available_minecraft_versions"
-            )));
-        }
-        info!(
-            "Loaded {} Minecraft versions",
-            available_minecraft_versions.len()
-        );
-        info!(
-            "Loaded {} Intermediary versions",
-            available_intermediary_versions.len()
-        );
+        _ => display_dialog(
+            t!("gui.error.loading"),
+            t!("gui.error.loading.intermediary_versions"),
+        ),
+    }
+    if minecraft.is_empty() {
+        return Err(InstallerError::from(t!(
+            "gui.error.no_available_minecraft_versions"
+        )));
+    }
+    info!("Loaded {} Minecraft versions", minecraft.len());
+    info!("Loaded {} Intermediary versions", intermediary_ids.len());
 
-        match loader_future.await {
-            Ok(versions) => {
-                available_loader_versions = versions;
-            }
-            _ => display_dialog(
-                t!("gui.error.loading"),
-                t!("gui.error.loading.loader_versions"),
-            ),
+    match loader_future.await {
+        Ok(versions) => {
+            loaders = versions;
         }
-        info!(
-            "Loaded versions for {} loaders",
-            available_loader_versions.len()
-        );
+        _ => display_dialog(
+            t!("gui.error.loading"),
+            t!("gui.error.loading.loader_versions"),
+        ),
+    }
+    info!("Loaded versions for {} loaders", loaders.len());
+    Ok(LoadedVersions {
+        minecraft,
+        intermediary,
+        intermediary_ids,
+        loaders,
+    })
+}
+
+impl App {
+    fn create() -> App {
         #[cfg(target_arch = "wasm32")]
         let app_canvas = {
             use eframe::wasm_bindgen::JsCast as _;
@@ -415,19 +530,16 @@ impl App {
         let mut app = App {
             mode: Mode::Client,
             selected_minecraft_version: String::new(),
-            available_minecraft_versions,
-            intermediary_versions,
-            available_intermediary_versions,
+            available_minecraft_versions: Vec::new(),
+            intermediary_versions: HashMap::new(),
+            available_intermediary_versions: Vec::new(),
             filtered_minecraft_versions: Vec::new(),
             show_snapshots: false,
             show_historical: false,
             selected_loader_type: LoaderType::Fabric,
-            selected_loader_version: available_loader_versions
-                .get(&LoaderType::Fabric)
-                .map(|v| v.first().unwrap().version.clone())
-                .unwrap_or(String::new()),
-            available_loader_versions,
-            show_betas: false,
+            selected_loader_version: String::new(),
+            available_loader_versions: HashMap::new(),
+            loader_channel: LoaderChannel::Stable,
             create_profile: true,
             client_install_location: super::dot_minecraft_location(),
             mmc_output_location: super::current_location(),
@@ -442,8 +554,18 @@ impl App {
             #[cfg(not(target_arch = "wasm32"))]
             detonation_easter_egg: rand::random_bool(0.001),
             include_flap: true,
+            versions_loading: None,
+            versions_error: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            versions_status: (None, None),
             modals: Vec::new(),
             modal_channel: std::sync::mpsc::channel(),
+            #[cfg(not(target_arch = "wasm32"))]
+            existing_install_channel: std::sync::mpsc::channel(),
+            #[cfg(not(target_arch = "wasm32"))]
+            existing_install: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            settings: super::settings::Settings::load(),
             #[cfg(target_arch = "wasm32")]
             app_canvas,
             request_main_content_sizing_pass: true,
@@ -452,8 +574,123 @@ impl App {
             #[cfg(target_arch = "wasm32")]
             small_viewport: false,
         };
-        app.filter_minecraft_versions();
-        Ok(app)
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(channel) = app
+            .settings
+            .loader_channel
+            .as_deref()
+            .and_then(LoaderChannel::from_id)
+        {
+            app.loader_channel = channel;
+        }
+        app.load_versions();
+        app
+    }
+
+    fn load_versions(&mut self) {
+        let (send, rec) = std::sync::mpsc::channel();
+        self.versions_loading = Some(rec);
+        self.versions_error = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        let fut = {
+            let (status_send, status_rec) = unbounded_channel();
+            self.versions_status = (Some(status_rec), None);
+            async move {
+                let _ = send.send(net::with_status(status_send, load_versions()).await);
+            }
+        };
+        #[cfg(target_arch = "wasm32")]
+        let fut = async move {
+            let _ = send.send(load_versions().await);
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::spawn(fut);
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(fut);
+    }
+
+    fn poll_versions(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(status), step) = &mut self.versions_status {
+            while let Ok((_, message)) = status.try_recv() {
+                *step = Some(message);
+            }
+        }
+        let Some(rec) = &self.versions_loading else {
+            return;
+        };
+        let result = match rec.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Err(InstallerError::from(t!(
+                "gui.error.no_available_minecraft_versions"
+            ))),
+        };
+        self.versions_loading = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.versions_status = (None, None);
+        }
+        self.request_main_content_sizing_pass = true;
+        match result {
+            Ok(versions) => {
+                self.selected_loader_version = versions
+                    .loaders
+                    .get(&self.selected_loader_type)
+                    .and_then(|v| self.loader_channel.default_version(v))
+                    .map(|v| v.version.clone())
+                    .unwrap_or_default();
+                self.available_minecraft_versions = versions.minecraft;
+                self.intermediary_versions = versions.intermediary;
+                self.available_intermediary_versions = versions.intermediary_ids;
+                self.available_loader_versions = versions.loaders;
+                self.filter_minecraft_versions();
+            }
+            Err(e) => {
+                error!("{}", e.0);
+                self.versions_error = Some(e.0);
+            }
+        }
+    }
+
+    fn add_loading_state(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            ui.set_min_width(400.0);
+            match &self.versions_error {
+                None => {
+                    ui.spinner();
+                    ui.label(t!("gui.ui.loading_versions"));
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if let Some(step) = &self.versions_status.1 {
+                        ui.label(step);
+                    }
+                }
+                Some(error) => {
+                    ui.label(RichText::new(t!("gui.error.loading")).strong());
+                    ui.label(error);
+                    ui.add_space(10.0);
+                    if ui
+                        .button(RichText::new(t!("gui.button.retry")).heading())
+                        .clicked()
+                    {
+                        self.load_versions();
+                        self.request_main_content_sizing_pass = true;
+                    }
+                    // Loads the versions from the cached responses without waiting on the network
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if !crate::cache::offline()
+                        && crate::cache::has_metadata()
+                        && ui
+                            .button(RichText::new(t!("gui.button.work_offline")).heading())
+                            .clicked()
+                    {
+                        crate::cache::set_offline(true);
+                        self.load_versions();
+                        self.request_main_content_sizing_pass = true;
+                    }
+                }
+            }
+        });
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -511,6 +748,27 @@ impl App {
                     ctx.request_repaint();
                 });
             }
+            let (location, recent) = match self.mode {
+                Mode::Client => (
+                    &mut self.client_install_location,
+                    &self.settings.recent_client,
+                ),
+                Mode::Server => (
+                    &mut self.server_install_location,
+                    &self.settings.recent_server,
+                ),
+                Mode::PrismLauncher => (&mut self.mmc_output_location, &self.settings.recent_prism),
+            };
+            if !recent.is_empty() {
+                ComboBox::from_id_salt("recent_locations")
+                    .width(20.0)
+                    .selected_text(t!("gui.ui.recent_locations"))
+                    .show_ui(ui, |ui| {
+                        for entry in recent {
+                            ui.selectable_value(location, entry.clone(), entry);
+                        }
+                    });
+            }
         });
     }
 
@@ -526,15 +784,21 @@ impl App {
                 .labelled_by(environment_label.id)
                 .clicked();
 
-            clicked |= ui
-                .radio_value(&mut self.mode, Mode::PrismLauncher, t!("gui.mode.prism"))
-                .labelled_by(environment_label.id)
-                .clicked();
+            #[cfg(feature = "mmc")]
+            {
+                clicked |= ui
+                    .radio_value(&mut self.mode, Mode::PrismLauncher, t!("gui.mode.prism"))
+                    .labelled_by(environment_label.id)
+                    .clicked();
+            }
 
-            clicked |= ui
-                .radio_value(&mut self.mode, Mode::Server, t!("gui.mode.server"))
-                .labelled_by(environment_label.id)
-                .clicked();
+            #[cfg(feature = "server")]
+            {
+                clicked |= ui
+                    .radio_value(&mut self.mode, Mode::Server, t!("gui.mode.server"))
+                    .labelled_by(environment_label.id)
+                    .clicked();
+            }
 
             if clicked && prev_mode != self.mode {
                 self.filter_minecraft_versions();
@@ -622,7 +886,24 @@ impl App {
         );
     }
 
+    /// Runs before the dropdown is drawn so it never shows a version that is corrected afterwards.
+    fn update_loader_version(&mut self, reset: bool) {
+        let versions = self
+            .available_loader_versions
+            .get(&self.selected_loader_type)
+            .unwrap();
+        let offered = versions
+            .iter()
+            .any(|v| v.version == self.selected_loader_version && self.loader_channel.includes(v));
+        if (reset || !offered)
+            && let Some(default) = self.loader_channel.default_version(versions)
+        {
+            self.selected_loader_version = default.version.clone();
+        }
+    }
+
     fn add_loader(&mut self, ui: &mut egui::Ui) {
+        self.update_loader_version(false);
         let loader_label = ui.label(t!("gui.ui.loader"));
         ui.horizontal(|ui| {
             let loader_type_response = ComboBox::from_id_salt("loader_type")
@@ -656,6 +937,9 @@ impl App {
                     changed
                 });
             loader_type_response.response.labelled_by(loader_label.id);
+            if loader_type_response.inner.is_some_and(|t| t) {
+                self.update_loader_version(true);
+            }
             let loader_version_label = ui.label(t!("gui.ui.loader_version"));
             ComboBox::from_id_salt("loader_version")
                 .height(130.0)
@@ -666,7 +950,7 @@ impl App {
                         .get(&self.selected_loader_type)
                         .unwrap()
                     {
-                        if self.show_betas || ele.is_stable() {
+                        if self.loader_channel.includes(ele) {
                             ui.selectable_value(
                                 &mut self.selected_loader_version,
                                 ele.version.clone(),
@@ -677,33 +961,122 @@ impl App {
                 })
                 .response
                 .labelled_by(loader_version_label.id);
-            let checkbox_response =
-                ui.checkbox(&mut self.show_betas, t!("gui.ui.show_loader_betas"));
-            if self
-                .available_loader_versions
-                .get(&self.selected_loader_type)
-                .unwrap()
-                .iter()
-                .find(|v| v.version == self.selected_loader_version)
-                .is_none()
-                || checkbox_response.clicked()
-                || loader_type_response.inner.is_some_and(|t| t)
-            {
-                self.selected_loader_version = self
-                    .available_loader_versions
-                    .get(&self.selected_loader_type)
-                    .unwrap()
-                    .iter()
-                    .filter(|v| self.show_betas || v.is_stable())
-                    .map(|v| v.version.clone())
-                    .next()
-                    .unwrap()
-                    .clone();
+            let mut channel_clicked = false;
+            for (channel, name) in [
+                (LoaderChannel::Stable, t!("gui.ui.loader_channel.stable")),
+                (LoaderChannel::Beta, t!("gui.ui.loader_channel.beta")),
+                (LoaderChannel::All, t!("gui.ui.loader_channel.all")),
+            ] {
+                channel_clicked |= ui
+                    .radio_value(&mut self.loader_channel, channel, name)
+                    .labelled_by(loader_label.id)
+                    .clicked();
+            }
+            if channel_clicked {
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    self.settings.loader_channel = Some(self.loader_channel.id().to_owned());
+                    self.settings.save();
+                }
+                // The version dropdown was already drawn this frame, draw it again right away
+                self.update_loader_version(true);
+                ui.ctx().request_repaint();
             }
         });
     }
 
+    /// Only looked up again when the selection changes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn existing_install(&mut self) -> Option<(String, u32)> {
+        if self.mode != Mode::Client {
+            return None;
+        }
+        let key = format!(
+            "{}|{}|{}|{}",
+            self.client_install_location,
+            self.selected_minecraft_version,
+            self.selected_loader_type.get_name(),
+            self.selected_loader_version
+        );
+        if let Some((checked, install)) = &self.existing_install
+            && *checked == key
+        {
+            return install.clone();
+        }
+        let install = crate::actions::client::find_exact_install(
+            Path::new(&self.client_install_location),
+            &self.selected_minecraft_version,
+            &self.selected_loader_type,
+            &self.selected_loader_version,
+        );
+        self.existing_install = Some((key, install.clone()));
+        install
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn confirm_reinstall(&mut self) {
+        let choice = self.existing_install_channel.0.clone();
+        self.modals.push(ModalPopup::custom(
+            t!("gui.dialog.already_installed"),
+            t!(
+                "gui.dialog.already_installed.message",
+                version = self.selected_minecraft_version,
+                loader = self.selected_loader_type.get_localized_name(),
+                loader_version = self.selected_loader_version
+            ),
+            MessageButtons::YesNoCancelCustom(
+                t!("gui.button.reinstall").into(),
+                t!("gui.button.update_profile").into(),
+                t!("gui.button.cancel").into(),
+            ),
+            Box::new(move |res| {
+                let _ = match res {
+                    MessageDialogResult::Yes => choice.send(ExistingInstallChoice::Reinstall),
+                    MessageDialogResult::No => choice.send(ExistingInstallChoice::UpdateProfile),
+                    _ => Ok(()),
+                };
+            }),
+        ));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run_profile_update(&mut self, profile_name: String, generation: u32) {
+        let (sender, receiver) = unbounded_channel();
+        let location = PathBuf::from(&self.client_install_location);
+        let minecraft_version = self.selected_minecraft_version.clone();
+        let loader_type = self.selected_loader_type.clone();
+        self.installation_task = Some(InstallationProgress::new((
+            receiver,
+            tokio::spawn(async move {
+                crate::actions::client::update_profile(
+                    sender,
+                    location,
+                    profile_name,
+                    &minecraft_version,
+                    loader_type,
+                    generation,
+                )
+            }),
+        )));
+    }
+
     fn run_installation(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (list, location) = match self.mode {
+                Mode::Client => (
+                    &mut self.settings.recent_client,
+                    &self.client_install_location,
+                ),
+                Mode::Server => (
+                    &mut self.settings.recent_server,
+                    &self.server_install_location,
+                ),
+                Mode::PrismLauncher => (&mut self.settings.recent_prism, &self.mmc_output_location),
+            };
+            super::settings::remember(list, location);
+            self.settings.save();
+        }
         if let Some(version) = self
             .available_minecraft_versions
             .iter()
@@ -747,17 +1120,24 @@ impl App {
                     if !create_profile {
                         let _ = sender.send((0.0, t!("gui.message.not_creating_profile").into()));
                     }
-                    let fut = crate::actions::client::install(
-                        sender,
-                        selected_version,
-                        intermediary_version,
-                        loader_type,
-                        loader_version,
-                        None,
-                        location,
-                        create_profile,
-                        include_flap,
-                    );
+                    let fut = async move {
+                        crate::actions::client::install(
+                            sender,
+                            selected_version,
+                            intermediary_version,
+                            loader_type,
+                            loader_version,
+                            None,
+                            location,
+                            create_profile,
+                            include_flap,
+                            false,
+                            None,
+                            false,
+                        )
+                        .await
+                        .map(|_| ())
+                    };
 
                     #[cfg(target_arch = "wasm32")]
                     {
@@ -769,7 +1149,7 @@ impl App {
                                 .send((1.1, String::new()))
                                 .expect("failed to finish");
                             sender2.closed().await;
-                            App::post_installation(res, dialog_sender, Mode::Client);
+                            App::post_installation(res, dialog_sender, Mode::Client, None);
                         });
                     }
                     #[cfg(not(target_arch = "wasm32"))]
@@ -778,6 +1158,7 @@ impl App {
                             Some(InstallationProgress::new((receiver, tokio::spawn(fut))));
                     }
                 }
+                #[cfg(feature = "server")]
                 Mode::Server => {
                     let location = Path::new(&self.server_install_location).to_path_buf();
                     let download_server = self.download_minecraft_server;
@@ -802,7 +1183,7 @@ impl App {
                                 .send((1.1, String::new()))
                                 .expect("failed to finish");
                             sender2.closed().await;
-                            App::post_installation(res, dialog_sender, Mode::Server);
+                            App::post_installation(res, dialog_sender, Mode::Server, None);
                         })
                     }
                     #[cfg(not(target_arch = "wasm32"))]
@@ -811,6 +1192,7 @@ impl App {
                             Some(InstallationProgress::new((receiver, tokio::spawn(fut))));
                     }
                 }
+                #[cfg(feature = "mmc")]
                 Mode::PrismLauncher => {
                     let location = Path::new(&self.mmc_output_location).to_path_buf();
                     let copy_profile_path = self.copy_generated_location;
@@ -826,6 +1208,9 @@ impl App {
                         generate_zip,
                         None,
                         include_flap,
+                        crate::actions::prism_pack::OnExists::Abort,
+                        None,
+                        Default::default(),
                     );
                     #[cfg(target_arch = "wasm32")]
                     {
@@ -837,7 +1222,7 @@ impl App {
                                 .send((1.1, String::new()))
                                 .expect("failed to finish");
                             sender2.closed().await;
-                            App::post_installation(res, dialog_sender, Mode::PrismLauncher);
+                            App::post_installation(res, dialog_sender, Mode::PrismLauncher, None);
                         })
                     }
                     #[cfg(not(target_arch = "wasm32"))]
@@ -846,6 +1231,9 @@ impl App {
                             Some(InstallationProgress::new((receiver, tokio::spawn(fut))));
                     }
                 }
+                // Modes left out of this build cannot be selected
+                #[cfg(not(all(feature = "server", feature = "mmc")))]
+                _ => {}
             }
         } else {
             self.modals.push(ModalPopup::ok(
@@ -870,8 +1258,18 @@ impl App {
                 let (_, handle) = prog.task.take().unwrap();
                 let dialog_sender = self.modal_channel.0.clone();
                 let mode = self.mode;
+                let location = PathBuf::from(match mode {
+                    Mode::Client => &self.client_install_location,
+                    Mode::Server => &self.server_install_location,
+                    Mode::PrismLauncher => &self.mmc_output_location,
+                });
                 tokio::spawn(async move {
-                    App::post_installation(handle.await.unwrap(), dialog_sender, mode);
+                    App::post_installation(
+                        handle.await.unwrap(),
+                        dialog_sender,
+                        mode,
+                        Some(location),
+                    );
                 });
             }
         }
@@ -1017,6 +1415,11 @@ impl App {
             }
             if back.ui(ui).clicked() {
                 self.installation_task = None;
+                // The task may have installed or removed files, look the selection up again
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    self.existing_install = None;
+                }
             }
         });
     }
@@ -1050,6 +1453,7 @@ impl App {
         result: Result<(), InstallerError>,
         dialog_sender: Sender<ModalPopup>,
         mode: Mode,
+        location: Option<PathBuf>,
     ) {
         match result {
             Err(e) => {
@@ -1061,13 +1465,37 @@ impl App {
             }
             Ok(_) => {
                 let s = dialog_sender.clone();
-                let _ = dialog_sender.send(ModalPopup::yesno(
+                // The third button opens the install location, where there is one to open
+                let buttons = match &location {
+                    Some(_) => MessageButtons::YesNoCancelCustom(
+                        t!("gui.button.yes").into(),
+                        t!("gui.button.no").into(),
+                        t!("gui.button.open_folder").into(),
+                    ),
+                    None => MessageButtons::YesNo,
+                };
+                let _ = dialog_sender.send(ModalPopup::custom(
                     t!("gui.dialog.installation_successful"),
                     match mode {
                         Mode::Server => t!("gui.dialog.installation_successful.server.message"),
                         _ => t!("gui.dialog.installation_successful.message"),
                     },
+                    buttons,
                     Box::new(move |res| {
+                        if res == MessageDialogResult::Cancel {
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if let Some(location) = location
+                                && let Err(e) = crate::ui::open_folder(&location)
+                            {
+                                let _ = s.send(ModalPopup::ok(
+                                    t!("gui.error.failed_to_open_folder"),
+                                    e.0,
+                                ));
+                            }
+                            #[cfg(target_arch = "wasm32")]
+                            let _ = location;
+                            return;
+                        }
                         if (res == MessageDialogResult::Yes || res == MessageDialogResult::Ok)
                             && webbrowser::open(crate::OSL_MODRINTH_URL).is_err()
                         {
@@ -1131,12 +1559,22 @@ impl App {
             #[cfg(target_arch = "wasm32")]
             let install_text = t!("gui.button.install_web");
             #[cfg(not(target_arch = "wasm32"))]
-            let install_text = t!("gui.button.install");
+            let existing_install = self.existing_install();
+            #[cfg(not(target_arch = "wasm32"))]
+            let install_text = match existing_install {
+                Some(_) => t!("gui.button.reinstall"),
+                None => t!("gui.button.install"),
+            };
             if Button::new(RichText::new(install_text).heading())
                 .min_size(Vec2::new(100.0, 0.0))
                 .ui(ui)
                 .clicked()
             {
+                #[cfg(not(target_arch = "wasm32"))]
+                if existing_install.is_some() {
+                    self.confirm_reinstall();
+                    return;
+                }
                 self.run_installation();
             }
         });
@@ -1153,6 +1591,7 @@ impl eframe::App for App {
             style.interaction.selectable_labels = false;
         });
 
+        self.poll_versions();
         if let Ok(result) = self.file_picker_channel.1.try_recv() {
             self.file_picker_open = false;
             if let Some(result) = result {
@@ -1210,6 +1649,10 @@ impl eframe::App for App {
                         ui.add_enabled_ui(!self.file_picker_open, |ui| {
                             ui.vertical_centered(|ui| ui.heading(t!("gui.ui.title")));
                             ui.add_space(15.0);
+                            if self.versions_loading.is_some() || self.versions_error.is_some() {
+                                self.add_loading_state(ui);
+                                return;
+                            }
                             if self.installation_task.is_some() {
                                 self.add_output(ui);
                                 return;
@@ -1268,6 +1711,16 @@ impl eframe::App for App {
                 });
             });
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(choice) = self.existing_install_channel.1.try_recv() {
+            match (choice, self.existing_install()) {
+                (ExistingInstallChoice::Reinstall, _) => self.run_installation(),
+                (ExistingInstallChoice::UpdateProfile, Some((profile_name, generation))) => {
+                    self.run_profile_update(profile_name, generation)
+                }
+                (ExistingInstallChoice::UpdateProfile, None) => {}
+            }
+        }
         if let Ok(modal) = self.modal_channel.1.try_recv() {
             info!("Displaying dialog: {}: {}", modal.title, modal.message);
             self.modals.push(modal)
@@ -8,6 +8,9 @@ pub mod gui;
 #[cfg(feature = "gui")]
 mod font_loader;
 
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+mod settings;
+
 #[allow(unused)]
 fn home_dir() -> Option<PathBuf> {
     #[allow(deprecated)]
@@ -111,3 +114,14 @@ pub fn server_location() -> String {
 pub fn server_location() -> String {
     ".".to_owned()
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_folder(path: &std::path::Path) -> Result<(), crate::errors::InstallerError> {
+    opener::open(path).map_err(|e| {
+        crate::errors::InstallerError::from(t!(
+            "ui.error.failed_to_open_folder",
+            path = path.display(),
+            error = e
+        ))
+    })
+}
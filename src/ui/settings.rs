@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_RECENT_LOCATIONS: usize = 5;
+
+/// GUI state kept between runs. Install locations are listed most recent first.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub recent_client: Vec<String>,
+    pub recent_server: Vec<String>,
+    pub recent_prism: Vec<String>,
+    pub loader_channel: Option<String>,
+}
+
+impl Settings {
+    fn file() -> Option<PathBuf> {
+        directories::ProjectDirs::from("net", "OrnitheMC", "ornithe-installer")
+            .map(|dirs| dirs.config_dir().join("settings.json"))
+    }
+
+    pub fn load() -> Self {
+        Self::file()
+            .and_then(|file| std::fs::read(file).ok())
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let Some(file) = Self::file() else {
+            return;
+        };
+        let result = file
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&file, serde_json::to_vec_pretty(self)?));
+        if let Err(e) = result {
+            log::warn!("Failed to save GUI settings to {}: {e}", file.display());
+        }
+    }
+}
+
+pub fn remember(list: &mut Vec<String>, location: &str) {
+    if location.trim().is_empty() {
+        return;
+    }
+    list.retain(|entry| entry != location);
+    list.insert(0, location.to_owned());
+    list.truncate(MAX_RECENT_LOCATIONS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_most_recent_first() {
+        let mut list = Vec::new();
+        for location in ["a", "b", "c", "d", "e", "f", "c", ""] {
+            remember(&mut list, location);
+        }
+        assert_eq!(list, ["c", "f", "e", "d", "b"]);
+    }
+}